@@ -0,0 +1,127 @@
+//! Measures the casts and time [`BackfaceMode`] saves when picking a scene of 1000 cuboids.
+//!
+//! Run with `cargo bench -p bevy_picking_rapier --features diagnostics`. Every pointer casts one
+//! ray per update. Rays from outside the grid hit the front face of a cuboid, which is never a
+//! backface, so every mode casts once per ray. Hollow rays starting inside a cuboid first hit one
+//! of its faces from the inside, which [`BackfaceMode::All`] skips with a second cast, while
+//! [`BackfaceMode::TrimeshOnly`] and [`BackfaceMode::Off`] report it without casting again.
+
+use std::time::{Duration, Instant};
+
+use bevy_app::App;
+use bevy_math::Vec3;
+use bevy_rapier3d::prelude::*;
+use bevy_transform::prelude::*;
+use bevy_utils::Uuid;
+
+use bevy_picking_core::backend::prelude::*;
+use bevy_picking_rapier::{
+    diagnostics::{RapierBackendDiagnostics, RapierPickCounts},
+    testing::*,
+    BackfaceMode, RapierBackendSettings, RapierRayPointer,
+};
+
+/// The number of cuboids along each axis of the grid.
+const GRID_SIZE: usize = 10;
+/// The distance between the centers of neighbouring cuboids, which have half extents of `0.5`.
+const SPACING: f32 = 2.0;
+/// The number of updates timed for each case.
+const UPDATES: u32 = 200;
+
+/// The center of the cuboid at `(x, y, z)` in the grid.
+fn grid_position(x: usize, y: usize, z: usize) -> Vec3 {
+    Vec3::new(x as f32, y as f32, z as f32) * SPACING
+}
+
+/// Builds an app with 1000 cuboids in a grid, and a ray pointer at each column of the grid, facing
+/// down its negative z axis. With `start_inside`, each ray starts at the center of the front
+/// cuboid of its column, and otherwise in front of the grid.
+fn grid_app(start_inside: bool, backface_culling: BackfaceMode) -> App {
+    let mut app = test_app();
+    app.add_plugins(RapierBackendDiagnostics);
+    {
+        let mut settings = app.world.resource_mut::<RapierBackendSettings>();
+        settings.backface_culling = backface_culling;
+        // Rays starting inside a solid cuboid would hit it right at their origin instead.
+        settings.solid = !start_inside;
+    }
+    for x in 0..GRID_SIZE {
+        for y in 0..GRID_SIZE {
+            for z in 0..GRID_SIZE {
+                let transform = Transform::from_translation(grid_position(x, y, z));
+                app.world.spawn((
+                    Collider::cuboid(0.5, 0.5, 0.5),
+                    TransformBundle {
+                        local: transform,
+                        global: GlobalTransform::from(transform),
+                    },
+                ));
+            }
+            let front = grid_position(x, y, GRID_SIZE - 1);
+            let origin = if start_inside {
+                front
+            } else {
+                front + Vec3::Z * SPACING
+            };
+            let pointer = PointerId::Custom(Uuid::from_u128((x * GRID_SIZE + y) as u128 + 1));
+            app.world.spawn((
+                pointer,
+                RapierRayPointer {
+                    origin,
+                    direction: Vec3::NEG_Z,
+                },
+            ));
+        }
+    }
+    sync_colliders(&mut app);
+    app
+}
+
+/// Runs [`UPDATES`] updates of `app`, returning the rays cast in the last one, and the average
+/// time taken by each.
+fn measure(app: &mut App) -> (usize, Duration) {
+    // Warm up, so the first update's setup isn't timed.
+    app.update();
+    let start = Instant::now();
+    for _ in 0..UPDATES {
+        app.update();
+    }
+    let elapsed = start.elapsed() / UPDATES;
+    (app.world.resource::<RapierPickCounts>().ray_casts, elapsed)
+}
+
+fn main() {
+    let rays = GRID_SIZE * GRID_SIZE;
+    println!("{rays} rays into {} cuboids", GRID_SIZE.pow(3));
+    println!(
+        "{:<24} {:<12} {:>10} {:>14}",
+        "rays", "mode", "casts", "time/update"
+    );
+    for (name, start_inside) in [("from outside", false), ("hollow, from inside", true)] {
+        let mut casts_by_mode = Vec::new();
+        for mode in [
+            BackfaceMode::All,
+            BackfaceMode::TrimeshOnly,
+            BackfaceMode::Off,
+        ] {
+            let (casts, time) = measure(&mut grid_app(start_inside, mode));
+            println!(
+                "{name:<24} {:<12} {casts:>10} {time:>14.2?}",
+                format!("{mode:?}")
+            );
+            casts_by_mode.push(casts);
+        }
+        let [all, trimesh_only, off] = casts_by_mode[..] else {
+            unreachable!("every mode is measured");
+        };
+        // Limiting backface checks to trimeshes never casts more than turning them off.
+        assert_eq!(trimesh_only, rays);
+        assert_eq!(off, rays);
+        if start_inside {
+            // Each ray skips the inside of its first cuboid with a second cast.
+            assert_eq!(all, 2 * rays);
+        } else {
+            assert_eq!(all, rays);
+        }
+    }
+}
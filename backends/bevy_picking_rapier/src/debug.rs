@@ -0,0 +1,64 @@
+//! Gizmo visualization of the rays cast by the [`RapierBackend`](crate::RapierBackend).
+//!
+//! Add the [`RapierBackendDebug`] plugin to draw every ray the backend casts this frame. Rays that
+//! hit something are drawn in green up to the last hit, with a marker at each hit and a line along
+//! the hit normal. Rays that miss are drawn in red.
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_gizmos::prelude::*;
+use bevy_math::{Quat, Vec3};
+use bevy_render::prelude::*;
+
+use crate::update_hits;
+
+/// The length used to draw rays that miss and have no maximum distance.
+const UNBOUNDED_RAY_LENGTH: f32 = 1000.0;
+
+/// Draws the rays cast by the [`RapierBackend`](crate::RapierBackend) with gizmos.
+#[derive(Clone)]
+pub struct RapierBackendDebug;
+impl Plugin for RapierBackendDebug {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RapierDebugRays>()
+            .add_systems(PreUpdate, draw_debug_rays.after(update_hits));
+    }
+}
+
+/// A ray cast by [`update_hits`] during the latest update, and what it hit.
+#[derive(Debug, Clone)]
+pub struct DebugRay {
+    /// The world space origin of the ray.
+    pub origin: Vec3,
+    /// The normalized direction of the ray.
+    pub direction: Vec3,
+    /// The maximum distance the ray was cast.
+    pub max_distance: f32,
+    /// The position and normal of every reported hit along the ray, nearest first.
+    pub hits: Vec<(Vec3, Option<Vec3>)>,
+}
+
+/// Collects the rays cast by [`update_hits`]. Only present when [`RapierBackendDebug`] is added.
+#[derive(Debug, Default, Resource)]
+pub struct RapierDebugRays(pub Vec<DebugRay>);
+
+fn draw_debug_rays(rays: Res<RapierDebugRays>, mut gizmos: Gizmos) {
+    for ray in &rays.0 {
+        let Some((last_hit, _)) = ray.hits.last() else {
+            let length = if ray.max_distance.is_finite() && ray.max_distance < f32::MAX {
+                ray.max_distance
+            } else {
+                UNBOUNDED_RAY_LENGTH
+            };
+            gizmos.ray(ray.origin, ray.direction * length, Color::RED);
+            continue;
+        };
+        gizmos.line(ray.origin, *last_hit, Color::GREEN);
+        for (position, normal) in &ray.hits {
+            gizmos.sphere(*position, Quat::IDENTITY, 0.05, Color::GREEN);
+            if let Some(normal) = normal {
+                gizmos.ray(*position, *normal * 0.5, Color::BLUE);
+            }
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! Diagnostics measuring how much work the [`RapierBackend`](crate::RapierBackend) does.
+//!
+//! Add the [`RapierBackendDiagnostics`] plugin to record, every frame, how many times the backend's
+//! filtering was evaluated for a candidate collider, and how many rays it cast. These show up with
+//! the other diagnostics, for example when using `LogDiagnosticsPlugin`, and help judge whether
+//! [`RapierBackendSettings::require_markers`](crate::RapierBackendSettings::require_markers) or
+//! collision groups would pay off in dense scenes.
+
+use bevy_app::prelude::*;
+use bevy_diagnostic::{Diagnostic, DiagnosticId, Diagnostics, RegisterDiagnostic};
+use bevy_ecs::prelude::*;
+
+use crate::update_hits;
+
+/// Records diagnostics about the rays cast by the [`RapierBackend`](crate::RapierBackend).
+#[derive(Clone)]
+pub struct RapierBackendDiagnostics;
+
+impl RapierBackendDiagnostics {
+    /// The number of times the backend's filtering was evaluated for a candidate collider.
+    pub const PREDICATE_EVALUATIONS: DiagnosticId =
+        DiagnosticId::from_u128(0x5c3f_2e7a_91d4_4b6e_8a1f_03c9_d2b7_6e41);
+    /// The number of rays, or shapes, the backend cast, including those needed to skip backfaces
+    /// and to find further hits.
+    pub const RAY_CASTS: DiagnosticId =
+        DiagnosticId::from_u128(0x8d21_a64f_37be_4c09_b5e2_7f18_4a93_c05d);
+}
+
+impl Plugin for RapierBackendDiagnostics {
+    fn build(&self, app: &mut App) {
+        app.register_diagnostic(Diagnostic::new(
+            Self::PREDICATE_EVALUATIONS,
+            "rapier_pick_predicate_evaluations",
+            20,
+        ))
+        .register_diagnostic(Diagnostic::new(
+            Self::RAY_CASTS,
+            "rapier_pick_ray_casts",
+            20,
+        ))
+        .init_resource::<RapierPickCounts>()
+        .add_systems(PreUpdate, measure_pick_counts.after(update_hits));
+    }
+}
+
+/// The work done by [`update_hits`] during the latest update. Only present when
+/// [`RapierBackendDiagnostics`] is added.
+#[derive(Debug, Default, Clone, Resource)]
+pub struct RapierPickCounts {
+    /// The number of times the backend's filtering was evaluated for a candidate collider.
+    pub predicate_evaluations: usize,
+    /// The number of rays, or shapes, cast.
+    pub ray_casts: usize,
+}
+
+fn measure_pick_counts(counts: Res<RapierPickCounts>, mut diagnostics: Diagnostics) {
+    diagnostics.add_measurement(RapierBackendDiagnostics::PREDICATE_EVALUATIONS, || {
+        counts.predicate_evaluations as f64
+    });
+    diagnostics.add_measurement(RapierBackendDiagnostics::RAY_CASTS, || {
+        counts.ray_casts as f64
+    });
+}
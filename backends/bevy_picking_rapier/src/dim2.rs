@@ -0,0 +1,193 @@
+//! A 2d variant of the rapier backend, using `bevy_rapier2d` instead of `bevy_rapier3d`.
+//!
+//! Pointers are projected into the world with [`Camera::viewport_to_world_2d`], and any collider
+//! under that point is reported. This shares [`RapierBackendSettings`] and [`RapierPickable`] with
+//! the 3d backend, so entities are filtered the same way in both.
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_render::{prelude::*, view::RenderLayers};
+use bevy_transform::prelude::*;
+use bevy_window::PrimaryWindow;
+
+use bevy_picking_core::backend::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    apply_pick_rules, has_pick_rules, is_in_viewport,
+    raycast::{self, camera_layers, is_ignored, is_pickable},
+    register_shared_types, topmost_camera, validate_settings, viewport_position,
+    warn_unmarked_cameras, HitMode, RapierBackendSettings, RapierOrderCamera, RapierPickFilters,
+    RapierPickRuleResults, RapierPickSet, RapierPickTransparent, RapierPickable,
+    RapierPickableCamera, RapierUnpickable,
+};
+
+/// Adds the `rapier` 2d raycasting picking backend to your app.
+#[derive(Clone)]
+pub struct RapierBackend2d;
+impl Plugin for RapierBackend2d {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RapierBackendSettings>()
+            .init_resource::<RapierPickRuleResults<Collider>>()
+            .configure_sets(PreUpdate, RapierPickSet::CastRays.in_set(PickSet::Backend))
+            .add_systems(
+                PreUpdate,
+                (
+                    validate_settings,
+                    warn_unmarked_cameras,
+                    apply_pick_rules::<Collider>.run_if(has_pick_rules::<Collider>),
+                    update_hits_2d.in_set(RapierPickSet::CastRays),
+                )
+                    .chain()
+                    .in_set(PickSet::Backend),
+            );
+        register_shared_types(app);
+    }
+}
+
+/// Checks for 2d colliders under each [`PointerLocation`], then outputs [`PointerHits`].
+pub fn update_hits_2d(
+    pointers: Query<(&PointerId, &PointerLocation, Option<&RapierOrderCamera>)>,
+    primary_window_entity: Query<Entity, With<PrimaryWindow>>,
+    picking_cameras: Query<(
+        Entity,
+        &Camera,
+        &GlobalTransform,
+        Option<&RapierPickable>,
+        Option<&RenderLayers>,
+        Option<&RapierPickableCamera>,
+    )>,
+    pickables: Query<&Pickable>,
+    marked_targets: Query<&RapierPickable>,
+    unpickables: Query<(), With<RapierUnpickable>>,
+    transparent: Query<(), With<RapierPickTransparent>>,
+    disabled: Query<(), With<ColliderDisabled>>,
+    layers: Query<&RenderLayers>,
+    backend_settings: Res<RapierBackendSettings>,
+    rapier_context: Option<Res<RapierContext>>,
+    filters: RapierPickFilters<Collider>,
+    mut was_disabled: Local<bool>,
+    mut output_events: EventWriter<PointerHits>,
+) {
+    // While disabled, empty hits are still sent on the first update, to clear any hovers, but
+    // nothing is sent after that, even with `emit_empty_hits`.
+    let just_disabled = !backend_settings.enabled && !std::mem::replace(&mut *was_disabled, true);
+    if backend_settings.enabled {
+        *was_disabled = false;
+    } else if !just_disabled {
+        return;
+    }
+    // Skip all work while disabled, while physics is still loading, or when markers are required
+    // and nothing is marked, unless empty hits still need to be reported.
+    let nothing_pickable = !backend_settings.enabled
+        || rapier_context.is_none()
+        || (backend_settings.require_markers && marked_targets.is_empty());
+    if nothing_pickable && !backend_settings.emit_empty_hits && !just_disabled {
+        return;
+    }
+
+    let primary_window = primary_window_entity.get_single().ok();
+    for (pointer_id, pointer_location, order_camera) in pointers
+        .iter()
+        .filter(|(pointer_id, ..)| backend_settings.pointer_filter.allows(**pointer_id))
+    {
+        let pointer_location = match pointer_location.location() {
+            Some(l) => l,
+            None => continue,
+        };
+        let mut cameras: Vec<_> = picking_cameras
+            .iter()
+            .filter(|(_, camera, .., cam_settings)| {
+                RapierPickableCamera::allows(*cam_settings, camera)
+                    && is_in_viewport(camera, pointer_location, primary_window)
+            })
+            .filter(|(_, _, _, marker, ..)| marker.is_some() || !backend_settings.require_markers)
+            .filter_map(|(entity, camera, transform, _, layers, _)| {
+                let viewport_pos = viewport_position(camera, pointer_location)?;
+                camera
+                    .viewport_to_world_2d(transform, viewport_pos)
+                    .map(|world_pos| (entity, camera, world_pos, layers))
+            })
+            .collect();
+        if backend_settings.topmost_camera_only {
+            cameras = topmost_camera(cameras, |(_, camera, ..)| camera);
+        }
+        // A pointer outside of every viewport isn't picked through any camera, so report here that
+        // it hits nothing.
+        if cameras.is_empty() && (backend_settings.emit_empty_hits || just_disabled) {
+            output_events.send(PointerHits::new(
+                *pointer_id,
+                Vec::new(),
+                backend_settings.order_offset,
+            ));
+        }
+        for (cam_entity, camera, world_pos, cam_layers) in cameras {
+            let cam_layers = camera_layers(cam_layers, &backend_settings);
+            // Hits are reported as if made through the pointer's order camera, if it has one.
+            let (hit_camera, order) = order_camera
+                .and_then(|order_camera| picking_cameras.get(order_camera.0).ok())
+                .map_or((cam_entity, camera.order), |(entity, camera, ..)| {
+                    (entity, camera.order)
+                });
+            let predicate = |entity| {
+                is_pickable(
+                    entity,
+                    cam_layers,
+                    &backend_settings,
+                    &marked_targets,
+                    &unpickables,
+                    &layers,
+                    &pickables,
+                ) && (backend_settings.pick_disabled_colliders || !disabled.contains(entity))
+                    && filters.allows(entity)
+                    && !transparent.contains(entity)
+                    // There is nothing behind a point for an ignored entity to occlude.
+                    && !is_ignored(entity, &pickables)
+            };
+            let filter = query_filter(&backend_settings).predicate(&predicate);
+
+            let mut picks: Vec<(Entity, HitData)> = Vec::new();
+            match rapier_context.as_deref().filter(|_| !nothing_pickable) {
+                // Report the empty hits without casting.
+                None => (),
+                Some(rapier_context) => {
+                    // In 2d, the pointer is a point in the world, not a ray, so every collider
+                    // containing that point is hit right at it, and there is no surface to report
+                    // a normal for.
+                    let max_hits = match backend_settings.hit_mode {
+                        HitMode::Closest => 1,
+                        _ => backend_settings.max_hits,
+                    };
+                    rapier_context.intersections_with_point(world_pos, filter, |entity| {
+                        let hit_data =
+                            HitData::new(hit_camera, 0.0, Some(world_pos.extend(0.0)), None);
+                        picks.push((entity, hit_data));
+                        picks.len() < max_hits
+                    });
+                }
+            }
+
+            if !picks.is_empty() || backend_settings.emit_empty_hits || just_disabled {
+                let order = order as f32 + backend_settings.order_offset;
+                output_events.send(PointerHits::new(*pointer_id, picks, order));
+            }
+        }
+    }
+}
+
+/// Builds the 2d [`QueryFilter`] used for raycasts from the [`RapierBackendSettings`], without any
+/// predicate, by converting the filter the 3d backend builds from the same settings.
+fn query_filter(backend_settings: &RapierBackendSettings) -> QueryFilter<'static> {
+    let filter = raycast::query_filter(backend_settings);
+    // The 2d and 3d versions of rapier use the same bits for their flags and groups.
+    QueryFilter {
+        flags: QueryFilterFlags::from_bits_truncate(filter.flags.bits()),
+        groups: filter.groups.map(|groups| {
+            CollisionGroups::new(
+                Group::from_bits_truncate(groups.memberships.bits()),
+                Group::from_bits_truncate(groups.filters.bits()),
+            )
+        }),
+        ..QueryFilter::new()
+    }
+}
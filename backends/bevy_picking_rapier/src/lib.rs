@@ -6,63 +6,837 @@
 //! automatically shoot rays into the rapier scene and will be able to pick things.
 //!
 //! To ignore an entity, you can add [`Pickable::IGNORE`] to it, and it will be ignored during
-//! raycasting.
+//! raycasting. To ignore it in this backend only, add [`RapierUnpickable`] instead. To have ignored
+//! entities still hide what is behind them, see [`RapierBackendSettings::ignore_mode`].
 //!
 //! For fine-grained control, see the [`RapierBackendSettings::require_markers`] setting.
 //!
+//! ## 2D
+//!
+//! Enable the `dim2` cargo feature and add the `RapierBackend2d` plugin to pick `bevy_rapier2d`
+//! colliders instead. It uses the same settings and markers as the 3d backend.
+//!
+//! ## Render targets
+//!
+//! Cameras that render to an image are picked by pointers located on that image, for example a
+//! custom pointer whose [`Location`] is set from where another pointer hits an in-world screen.
+//! Pointers on a window never pick through cameras rendering to an image.
+//!
+//! The same goes for apps with multiple windows: each pointer's [`Location::target`] is matched
+//! against every camera's [`RenderTarget`](bevy_render::camera::RenderTarget) before any ray is
+//! cast, so a pointer only picks through the cameras rendering to the window it is on.
+//!
+//! ## Coordinates
+//!
+//! Pointer positions, camera viewports, and [`RapierBackendSettings::sample_radius`] are all
+//! handled in logical pixels, so picks land under the pointer regardless of the window's scale
+//! factor, as on HiDPI displays. A camera's physical [`Viewport`](bevy_render::camera::Viewport) is
+//! converted to logical pixels with the scale factor of its render target before the pointer is
+//! checked against it, and the ray is built with [`Camera::viewport_to_world`], which also works
+//! in logical pixels. Images have a scale factor of `1`, so there, logical and physical pixels are
+//! the same.
+//!
+//! ## Hit depth
+//!
+//! The [`HitData::depth`] of each hit is the distance in world units along the pointer's ray, from
+//! the point on the camera's near plane under the pointer to the hit. Backfaces skipped on the way
+//! and the small advances made past them don't change this, so it is always the plain geometric
+//! distance. With [`RapierBackendSettings::pick_shape`], it is the distance the shape travelled
+//! before touching the collider.
+//!
+//! ## Reacting to picks
+//!
+//! The [`PointerHits`] sent by this backend are turned into pointer events, such as
+//! `Pointer<Click>`, by `bevy_picking_core`, which bubble up the entity hierarchy. To react to picks
+//! on a single entity, add an `On::<Pointer<Click>>` event listener from `bevy_eventlistener` to
+//! it. Bevy's observers, triggered with `Trigger`, are only available in later versions of Bevy
+//! than this backend is built for, so the backend doesn't trigger them.
+//!
 //! ## Limitations
 //!
-//! Because raycasting is expensive, only the closest intersection will be reported. This means that
-//! unlike some UI, you cannot hover multiple rapier objects with a single pointer by configuring
-//! the [`Pickable`] component to not block lower elements but still emit events. As mentioned
-//! above, all that is supported is completely ignoring an entity with [`Pickable::IGNORE`].
+//! Because raycasting is expensive, only the closest intersection will be reported by default. This
+//! means that unlike some UI, you cannot hover multiple rapier objects with a single pointer by
+//! configuring the [`Pickable`] component to not block lower elements but still emit events. As
+//! mentioned above, all that is supported is completely ignoring an entity with
+//! [`Pickable::IGNORE`].
+//!
+//! If you do need to pick multiple entities stacked on top of each other, you can change the
+//! [`RapierBackendSettings::hit_mode`]. [`HitMode::UntilBlocking`] respects
+//! [`Pickable::should_block_lower`] like UI backends do, and [`HitMode::All`] reports everything
+//! under the pointer. Both cast one ray per intersected entity, up to
+//! [`RapierBackendSettings::max_hits`], so the cost grows with the number of colliders under the
+//! pointer.
+//!
+//! The backend casts into the single [`RapierContext`] resource. The version of `bevy_rapier` this
+//! backend targets has no support for multiple physics worlds, so there is no way to pick against
+//! a second, non-default context. To keep isolated sub-scenes apart within the one context, give
+//! their colliders and cameras different [`RenderLayers`], which the backend already uses to filter
+//! hits per camera.
 //!
-//! This is probably not a meaningful limitation, as the feature is usually only used in UI where
-//! you might want a pointer to be able to pick multiple elements that are on top of each other. If
-//! are trying to build a UI out of rapier entities, beware, I suppose.
+//! This backend is built against `bevy_rapier` 0.23, where the [`RapierContext`] is always a
+//! resource. Later versions of `bevy_rapier` move contexts onto entities, linked to with a
+//! `RapierContextEntityLink`. Those versions are not supported: they need a newer version of Bevy,
+//! so they can't be used in the same app as this backend.
 
 #![allow(clippy::type_complexity)]
 #![allow(clippy::too_many_arguments)]
 #![deny(missing_docs)]
 
+use std::marker::PhantomData;
+
 use bevy_app::prelude::*;
-use bevy_ecs::prelude::*;
+use bevy_ecs::{prelude::*, system::SystemParam};
+use bevy_math::{Ray, Vec2, Vec3};
 use bevy_reflect::{std_traits::ReflectDefault, Reflect};
 use bevy_render::{prelude::*, view::RenderLayers};
+use bevy_tasks::{ComputeTaskPool, ParallelSlice, TaskPool};
 use bevy_transform::prelude::*;
+use bevy_utils::{tracing::warn, HashMap, HashSet};
 use bevy_window::PrimaryWindow;
 
-use bevy_picking_core::backend::prelude::*;
-use bevy_rapier3d::{na::{Const, OPoint}, prelude::*};
+use bevy_picking_core::{backend::prelude::*, pointer::Location};
+use bevy_rapier3d::{
+    prelude::*,
+    rapier::{
+        geometry::{Collider as RawCollider, Ray as RapierRay},
+        math::{Point, Real, Vector},
+        prelude::{ColliderHandle, FeatureId, RigidBodyHandle},
+    },
+};
 
 // Re-export for uses who want this
+#[cfg(feature = "dim2")]
+pub use bevy_rapier2d;
 pub use bevy_rapier3d;
 
+#[cfg(feature = "debug")]
+pub mod debug;
+#[cfg(feature = "diagnostics")]
+pub mod diagnostics;
+#[cfg(feature = "dim2")]
+pub mod dim2;
+mod raycast;
+#[cfg(feature = "testing")]
+pub mod testing;
+
+use raycast::{ray_advance, PickRay, RayHit};
+pub use raycast::{RapierPickFilters, RapierPicker};
+
 /// Commonly used imports.
 pub mod prelude {
-    pub use crate::{RapierBackend, RapierBackendSettings, RapierPickable};
+    #[cfg(feature = "debug")]
+    pub use crate::debug::RapierBackendDebug;
+    #[cfg(feature = "diagnostics")]
+    pub use crate::diagnostics::RapierBackendDiagnostics;
+    #[cfg(feature = "dim2")]
+    pub use crate::dim2::RapierBackend2d;
+    pub use crate::{
+        BackfaceMode, ConfiguredRapierBackend, HitMode, HitSelection, IgnoreMode, PickRule,
+        PointerFilter, RapierBackend, RapierBackendSettings, RapierDepthBias, RapierHitSpan,
+        RapierHovered, RapierMissPoint, RapierMissPoints, RapierOrderCamera,
+        RapierPendingColliders, RapierPickAnchor, RapierPickExclude, RapierPickFilter,
+        RapierPickGroups, RapierPickPriority, RapierPickRayOverride, RapierPickRays,
+        RapierPickRule, RapierPickRuleResults, RapierPickSet, RapierPickTransparent,
+        RapierPickable, RapierPickableCamera, RapierPicker, RapierPointerHit, RapierPointerHits,
+        RapierRayPointer, RapierUnpickable, SensorMode, TrimeshHit,
+    };
 }
 
 /// Adds the `rapier` raycasting picking backend to your app.
+///
+/// The backend starts with the [`RapierBackendSettings`] already in the app, or the default
+/// settings. To configure it where it is added instead, use [`RapierBackend::with_settings`] or
+/// [`ConfiguredRapierBackend`].
 #[derive(Clone)]
 pub struct RapierBackend;
+
+impl RapierBackend {
+    /// Creates a [`ConfiguredRapierBackend`] that starts with `settings`.
+    pub fn with_settings(settings: RapierBackendSettings) -> ConfiguredRapierBackend {
+        ConfiguredRapierBackend { settings }
+    }
+}
+
 impl Plugin for RapierBackend {
     fn build(&self, app: &mut App) {
-        app.init_resource::<RapierBackendSettings>()
-            .add_systems(PreUpdate, update_hits.in_set(PickSet::Backend))
-            .register_type::<RapierBackendSettings>()
-            .register_type::<RapierPickable>();
+        app.init_resource::<RapierBackendSettings>();
+        build_backend(app);
     }
 }
 
+/// Adds the [`RapierBackend`] to your app, carrying the [`RapierBackendSettings`] to start with, so
+/// the backend is configured where it is added:
+///
+/// ```
+/// # use bevy_picking_rapier::ConfiguredRapierBackend;
+/// let backend = ConfiguredRapierBackend::default()
+///     .require_markers(true)
+///     .max_ray_distance(100.0);
+/// ```
+///
+/// When using `DefaultPickingPlugins`, disable the default backend with
+/// `DefaultPickingPlugins.build().disable::<RapierBackend>()`, and add this plugin instead. Its
+/// settings replace any [`RapierBackendSettings`] resource already in the app.
+#[derive(Clone, Default)]
+pub struct ConfiguredRapierBackend {
+    settings: RapierBackendSettings,
+}
+
+impl ConfiguredRapierBackend {
+    /// Sets [`RapierBackendSettings::require_markers`].
+    pub fn require_markers(mut self, require_markers: bool) -> Self {
+        self.settings.require_markers = require_markers;
+        self
+    }
+
+    /// Sets [`RapierBackendSettings::hit_mode`].
+    pub fn hit_mode(mut self, hit_mode: HitMode) -> Self {
+        self.settings.hit_mode = hit_mode;
+        self
+    }
+
+    /// Sets [`RapierBackendSettings::max_ray_distance`].
+    pub fn max_ray_distance(mut self, max_ray_distance: f32) -> Self {
+        self.settings.max_ray_distance = Some(max_ray_distance);
+        self
+    }
+
+    /// Sets [`RapierBackendSettings::groups`].
+    pub fn groups(mut self, groups: CollisionGroups) -> Self {
+        self.settings.groups = Some(groups);
+        self
+    }
+
+    /// Sets [`RapierBackendSettings::sensor_mode`].
+    pub fn sensor_mode(mut self, sensor_mode: SensorMode) -> Self {
+        self.settings.sensor_mode = sensor_mode;
+        self
+    }
+}
+
+impl Plugin for ConfiguredRapierBackend {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.settings.clone());
+        build_backend(app);
+    }
+}
+
+/// Adds everything but the [`RapierBackendSettings`] of the [`RapierBackend`] to `app`.
+fn build_backend(app: &mut App) {
+    app.init_resource::<RapierHovered>()
+        .init_resource::<RapierMissPoints>()
+        .init_resource::<RapierPendingColliders>()
+        .init_resource::<RapierPickRays>()
+        .init_resource::<RapierPickRuleResults<Collider>>()
+        .init_resource::<RapierPointerHits>()
+        .add_event::<RapierPointerHit>()
+        .configure_sets(PreUpdate, RapierPickSet::CastRays.in_set(PickSet::Backend))
+        .add_systems(
+            PreUpdate,
+            (
+                validate_settings,
+                warn_unmarked_cameras,
+                apply_pick_rules::<Collider>.run_if(has_pick_rules::<Collider>),
+                track_pending_colliders,
+                update_hits.in_set(RapierPickSet::CastRays),
+            )
+                .chain()
+                .in_set(PickSet::Backend),
+        )
+        .register_type::<RapierPickRayOverride>()
+        .register_type::<RapierPickExclude>()
+        .register_type::<RapierPickGroups>()
+        .register_type::<RapierPickAnchor>()
+        .register_type::<RapierRayPointer>()
+        .register_type::<Vec<Group>>()
+        .register_type::<RapierPickPriority>()
+        .register_type::<RapierDepthBias>();
+    register_shared_types(app);
+}
+
+/// Registers the types shared by the 3d and 2d backends, including every type nested in the
+/// [`RapierBackendSettings`], so that inspectors can display and edit all of the settings.
+pub(crate) fn register_shared_types(app: &mut App) {
+    app.register_type::<RapierBackendSettings>()
+        .register_type::<RapierPickable>()
+        .register_type::<RapierUnpickable>()
+        .register_type::<RapierPickableCamera>()
+        .register_type::<RapierPickTransparent>()
+        .register_type::<RapierOrderCamera>()
+        .register_type::<BackfaceMode>()
+        .register_type::<HitMode>()
+        .register_type::<HitSelection>()
+        .register_type::<PointerFilter>()
+        .register_type::<SensorMode>()
+        .register_type::<IgnoreMode>()
+        .register_type::<Option<f32>>()
+        .register_type::<Option<Vec3>>()
+        .register_type::<Option<CollisionGroups>>()
+        .register_type::<Vec<PointerId>>()
+        .register_type::<Vec<Entity>>();
+}
+
+/// Labels the systems of the rapier backends, so your own systems can be ordered around them.
+///
+/// These sets are inside [`PickSet::Backend`]. To process the rapier hits after they are sent, but
+/// before the picking core reads them, add your system to [`PickSet::Backend`] as well:
+///
+/// ```
+/// # use bevy_app::prelude::*;
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_picking_core::PickSet;
+/// # use bevy_picking_rapier::RapierPickSet;
+/// # fn my_system() {}
+/// # let mut app = App::new();
+/// app.add_systems(
+///     PreUpdate,
+///     my_system
+///         .after(RapierPickSet::CastRays)
+///         .in_set(PickSet::Backend),
+/// );
+/// ```
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
+pub enum RapierPickSet {
+    /// Casts the pick rays and sends [`PointerHits`].
+    CastRays,
+}
+
 /// Runtime settings for the [`RapierBackend`].
-#[derive(Resource, Default, Reflect)]
+///
+/// The 2d backend uses the same settings, but 2d picks are points, not rays with a depth and a
+/// normal, so it only uses the settings that decide what can be picked and how hits are reported:
+/// [`require_markers`](Self::require_markers), [`enabled`](Self::enabled),
+/// [`pointer_filter`](Self::pointer_filter), [`hit_mode`](Self::hit_mode),
+/// [`max_hits`](Self::max_hits), [`emit_empty_hits`](Self::emit_empty_hits),
+/// [`groups`](Self::groups), the render layers settings,
+/// [`warn_on_misconfig`](Self::warn_on_misconfig),
+/// [`pick_disabled_colliders`](Self::pick_disabled_colliders), [`sensor_mode`](Self::sensor_mode),
+/// [`ignore_mode`](Self::ignore_mode), the body type exclusions,
+/// [`order_offset`](Self::order_offset), and [`topmost_camera_only`](Self::topmost_camera_only).
+/// Every other setting is only used by the 3d backend.
+#[derive(Clone, Resource, Reflect)]
 #[reflect(Resource, Default)]
 pub struct RapierBackendSettings {
     /// When set to `true` raycasting will only happen between cameras and entities marked with
     /// [`RapierPickable`]. Off by default. This setting is provided to give you fine-grained
     /// control over which cameras and entities should be used by the rapier backend at runtime.
     pub require_markers: bool,
+    /// When set to `false`, the backend stops picking entirely, for example during a cutscene.
+    /// Empty [`PointerHits`] are sent on the first update while disabled, so that nothing stays
+    /// hovered, and nothing is sent after that until the backend is enabled again, even with
+    /// [`RapierBackendSettings::emit_empty_hits`]. On by default.
+    pub enabled: bool,
+    /// Controls which pointers rays are cast for. See [`PointerFilter`].
+    pub pointer_filter: PointerFilter,
+    /// Controls which of the entities along the ray are reported. See [`HitMode`].
+    pub hit_mode: HitMode,
+    /// Selects whether the nearest or the farthest hits are reported. See [`HitSelection`].
+    pub selection: HitSelection,
+    /// The maximum number of hits reported per pointer and camera when the
+    /// [`RapierBackendSettings::hit_mode`] reports more than the closest hit. This bounds the
+    /// number of raycasts performed when many colliders are stacked under the pointer. Defaults to
+    /// `16`.
+    pub max_hits: usize,
+    /// When set, up to this many of the nearest distinct entities along each ray are reported, no
+    /// matter the [`RapierBackendSettings::hit_mode`], and in place of
+    /// [`RapierBackendSettings::max_hits`]. This is what a UI that cycles through overlapping
+    /// objects needs. `None` by default.
+    ///
+    /// The ray walks past each entity it hits, so an entity is only reported once, even when its
+    /// collider has several shapes along the ray, such as the children of a compound collider.
+    /// This takes one raycast per reported entity.
+    pub max_distinct_hits: Option<usize>,
+    /// The maximum distance, in world units along the ray, at which colliders can be picked.
+    /// Colliders farther away than this from the camera will not be hit. When `None`, the default,
+    /// rays are unbounded. This can be overridden per camera with [`RapierPickableCamera`].
+    pub max_ray_distance: Option<f32>,
+    /// When set to `true`, rays through cameras end at the camera's far clipping plane, so picks
+    /// match what the camera can see. This replaces [`RapierBackendSettings::max_ray_distance`] for
+    /// cameras with a [`Projection`], and is itself overridden by
+    /// [`RapierPickableCamera::max_distance`]. Rays are unbounded for cameras whose far plane is
+    /// infinitely far away. Off by default.
+    pub ray_distance_from_far_plane: bool,
+    /// The minimum distance, in world units along the ray, at which colliders can be picked. Rays
+    /// start this far from the camera, so colliders closer than this, such as the collider of a
+    /// character followed by a chase camera, are passed through. Defaults to `0.0`.
+    pub min_ray_distance: f32,
+    /// Passed to rapier as the `solid` parameter of raycasts. When `true`, the default, a ray that
+    /// starts inside a collider hits it immediately, at a depth of zero. When `false`, shapes are
+    /// treated as hollow, and the ray will instead hit the inside of the collider's boundary.
+    ///
+    /// Because the inside of a boundary faces away from the ray, that hit is a backface, and will
+    /// be skipped like any other backface. In practice, this means setting this to `false` lets
+    /// cameras placed inside a collider, such as a large trigger volume, pick what is behind it.
+    pub solid: bool,
+    /// When set to `true`, a [`PointerHits`] event with no picks is sent for every camera a pointer
+    /// is over, even when the ray misses everything. This lets you know exactly which frame a
+    /// pointer stopped hitting any collider. Off by default, in which case events are only sent
+    /// when something is hit. This also applies while there is no [`RapierContext`], such as while
+    /// physics is still loading, so that hovers are cleared. A pointer that is outside of every
+    /// camera's viewport is sent a single event with no picks, at order `0` plus the
+    /// [`RapierBackendSettings::order_offset`].
+    pub emit_empty_hits: bool,
+    /// When set to `true`, no [`PointerHits`] with no picks are sent for
+    /// [`RapierBackendSettings::emit_empty_hits`] while any collider is still being built from a
+    /// mesh, as listed in [`RapierPendingColliders`]. This keeps a pointer over an entity whose
+    /// collider isn't ready yet from being reported as hitting nothing. Off by default.
+    pub wait_for_async_colliders: bool,
+    /// When set, only colliders whose [`CollisionGroups`] are compatible with these groups will be
+    /// picked, using the same rules rapier uses for physics. This lets you reuse your existing
+    /// membership and filter masks, for example to make a dedicated "pickable" group. This is
+    /// applied in addition to all other filtering, such as [`RapierBackendSettings::require_markers`].
+    pub groups: Option<CollisionGroups>,
+    /// The render layers of entities without a [`RenderLayers`] component, when checking whether
+    /// they are visible to a camera. Defaults to [`RenderLayers::default`], layer `0`, as with
+    /// rendering.
+    pub default_entity_layers: RenderLayers,
+    /// The render layers of cameras without a [`RenderLayers`] component, when checking which
+    /// entities they can pick. Defaults to [`RenderLayers::all`], so such cameras can pick entities
+    /// on any layer.
+    pub default_camera_layers: RenderLayers,
+    /// Render layers that can't be picked through any camera, such as a background layer during a
+    /// cutscene. These are removed from the layers of every camera, so entities only on disabled
+    /// layers can't be picked, while entities also on another layer the camera sees still can.
+    /// Defaults to [`RenderLayers::none`].
+    pub disabled_layers: RenderLayers,
+    /// When set to `true`, the rapier specific details of each hit are kept in the
+    /// [`RapierPointerHits`] resource and sent as [`RapierPointerHit`] events. These are also
+    /// provided whenever any of the settings for their optional details is enabled, such as
+    /// [`RapierBackendSettings::provide_velocities`]. Off by default, so hits cost no more than
+    /// their [`HitData`].
+    pub provide_hit_details: bool,
+    /// When set to `true`, the position of each hit in the local space of the entity that was hit
+    /// is provided in [`RapierPointerHit::local_point`]. Off by default.
+    pub provide_local_points: bool,
+    /// When set to `true`, the velocity of the collider at each hit is provided in
+    /// [`RapierPointerHit::velocity`]. Off by default.
+    pub provide_velocities: bool,
+    /// When set to `true`, the child shape hit on compound colliders is provided in
+    /// [`RapierPointerHit::compound_child`]. This finds the child nearest to the hit, so it costs a
+    /// distance query per child of every compound that is hit. Off by default.
+    pub provide_compound_children: bool,
+    /// When set to `true`, the triangle hit on trimesh colliders is provided in
+    /// [`RapierPointerHit::trimesh`]. Off by default.
+    pub provide_trimesh_hits: bool,
+    /// When set to `true`, the normal interpolated from the vertex normals of the triangle hit on
+    /// trimesh colliders is provided in [`RapierPointerHit::smooth_normal`]. Off by default.
+    pub provide_smooth_normals: bool,
+    /// When set to `true`, the normal of each hit is also provided in the view space of the camera
+    /// it was hit through, in [`RapierPointerHit::view_normal`]. Off by default.
+    pub provide_view_space_normals: bool,
+    /// When set to `false`, hits are reported without a [`HitData::normal`], and the normals of
+    /// hits aren't looked up or transformed, saving work in scenes with many pointers where normals
+    /// are unused. This also disables skipping backfaces, as if
+    /// [`RapierBackendSettings::backface_culling`] was [`BackfaceMode::Off`], and leaves out the
+    /// [`RapierPointerHit::view_normal`] and [`RapierPointerHit::smooth_normal`]. Defaults to
+    /// `true`.
+    pub compute_normals: bool,
+    /// When set to `true`, the depth of each hit in the normalized device coordinates of the camera
+    /// it was hit through is provided in [`RapierPointerHit::ndc_depth`], for comparison with the
+    /// depth buffer. Off by default.
+    pub provide_ndc_depths: bool,
+    /// When set to `true`, the point where each ray leaves the collider it entered is found, and
+    /// provided with the entry point in [`RapierPointerHit::span`], for example to measure the
+    /// thickness of a volume along the ray. This casts once more against the collider hit, only.
+    /// Off by default.
+    pub provide_exit_points: bool,
+    /// When set to `true`, the ray cast for each pointer and camera is recorded in the
+    /// [`RapierPickRays`] resource. Off by default.
+    pub record_rays: bool,
+    /// When set to `true`, the point at the maximum distance along each ray that doesn't hit
+    /// anything is recorded in the [`RapierMissPoints`] resource. Off by default. Rays without a
+    /// maximum distance are never recorded.
+    pub emit_max_distance_point: bool,
+    /// When set to `true`, a warning is logged the first time a ray can't be built through a
+    /// camera, for example because of a misconfigured projection or a zero sized viewport, which
+    /// gives a degenerate ray. Such cameras are otherwise silently skipped. Off by default.
+    pub log_ray_failures: bool,
+    /// When set to `true`, a warning is logged once if the settings are likely a mistake, such as
+    /// [`RapierBackendSettings::require_markers`] being set while no camera has a
+    /// [`RapierPickable`] marker, which silently disables all picking. On by default.
+    pub warn_on_misconfig: bool,
+    /// When set to `true`, entities with a [`ColliderDisabled`] component can be picked. Off by
+    /// default, in which case disabled colliders are never picked. Rapier itself may skip disabled
+    /// colliders during raycasts, so this can't guarantee they are hit.
+    pub pick_disabled_colliders: bool,
+    /// Controls whether sensor colliders can be picked. See [`SensorMode`].
+    pub sensor_mode: SensorMode,
+    /// Controls whether entities with [`Pickable::IGNORE`] block the entities behind them. See
+    /// [`IgnoreMode`].
+    pub ignore_mode: IgnoreMode,
+    /// When set to `true`, colliders attached to a fixed rigid body, or to no rigid body at all,
+    /// can't be picked. This is useful to only pick movable props, ignoring the static level
+    /// geometry. Off by default.
+    pub exclude_fixed: bool,
+    /// When set to `true`, colliders attached to a dynamic rigid body can't be picked. Off by
+    /// default.
+    pub exclude_dynamic: bool,
+    /// When set to `true`, colliders attached to a kinematic rigid body can't be picked. Off by
+    /// default.
+    pub exclude_kinematic: bool,
+    /// When set to `true`, the [`KinematicCharacterController::custom_shape`] of character
+    /// controllers can be picked. Off by default.
+    ///
+    /// Character controllers with a [`Collider`] on their entity are picked like any other
+    /// collider. A custom shape, however, is only used by the controller to move, and is not known
+    /// to rapier's query pipeline, so it is tested separately. This skips rapier's own filtering,
+    /// such as [`RapierBackendSettings::groups`] and the body type filters, and only applies to
+    /// rays, not to [`RapierBackendSettings::pick_shape`].
+    ///
+    /// A character controller without a rigid body is treated by rapier as attached to a fixed
+    /// body, so its collider is excluded by [`RapierBackendSettings::exclude_fixed`].
+    pub pick_controller_shapes: bool,
+    /// Controls which colliders are checked for backfaces, which rays step past instead of hitting.
+    /// See [`BackfaceMode`]. Set this to [`BackfaceMode::Off`] to report the first surface a ray
+    /// hits, backface or not.
+    pub backface_culling: BackfaceMode,
+    /// The number of consecutive backfaces a ray will step past before giving up. Each skipped
+    /// backface costs another raycast. Defaults to `1`, which is enough for a ray starting inside
+    /// a single hollow mesh; increase this for nested hollow meshes.
+    pub max_backface_skips: usize,
+    /// The minimum distance, in world units, a ray is advanced past a backface before it is cast
+    /// again. The advance also grows with the distance from the camera, so that it is never lost to
+    /// floating point precision and the ray always makes forward progress. Surfaces closer than
+    /// this behind a backface are stepped over, so keep it well below the size of the smallest
+    /// gaps in the scene. Defaults to `1e-4`.
+    pub ray_advance_bias: f32,
+    /// When set, this shape is swept along the pointer's ray instead of casting an infinitely thin
+    /// ray, giving the pointer a pick radius. A small ball, e.g. [`Collider::ball`], makes small
+    /// objects much easier to pick with a finger on a touchscreen. `None` by default.
+    ///
+    /// Shape casts don't report which feature of a collider was hit, so backfaces are not skipped
+    /// in this mode, and the [`RapierPointerHit::feature`] is [`FeatureId::Unknown`]. When
+    /// [`RapierBackendSettings::solid`] is `true`, a shape that starts out overlapping a collider
+    /// hits it immediately.
+    #[reflect(ignore)]
+    pub pick_shape: Option<Collider>,
+    /// When set, and a pointer's rays miss everything, the first collider that comes within this
+    /// distance of the ray, in world units, is reported instead. This makes small objects much more
+    /// forgiving to pick with touch input, without changing picks that hit directly. `None` by
+    /// default. This is only used when no [`RapierBackendSettings::pick_shape`] is set.
+    ///
+    /// The fallback sweeps a ball of this radius along the ray, costing one more shape cast for
+    /// every ray that misses. Shape casts don't report which feature of a collider was hit, so
+    /// these hits have a [`FeatureId::Unknown`] feature.
+    pub nearest_to_ray_tolerance: Option<f32>,
+    /// The number of rays cast for each pointer and camera. When this is more than `1`, rays are
+    /// also cast through `samples - 1` points evenly spaced on a circle of
+    /// [`RapierBackendSettings::sample_radius`] around the pointer, and the hits of the ray with
+    /// the nearest hit are reported, ties going to the ray through the pointer itself. This makes
+    /// small objects easier to pick without sweeping a [`RapierBackendSettings::pick_shape`], at
+    /// the cost of casting more rays. `1` by default, which only casts the ray through the pointer,
+    /// and `0` is treated the same way.
+    pub samples: u8,
+    /// The radius, in logical pixels, of the circle of extra rays cast around the pointer when
+    /// [`RapierBackendSettings::samples`] is more than `1`. `4.0` by default.
+    pub sample_radius: f32,
+    /// When set to `true`, rays for different pointers and cameras are cast in parallel on the
+    /// [`ComputeTaskPool`]. This is worth enabling when many pointers are active at once, such as
+    /// with multi-touch, but adds overhead when there are only a few. Off by default.
+    pub parallel: bool,
+    /// When set to `true`, the order of each [`PointerHits`] event is lowered slightly based on the
+    /// depth of its nearest hit, so that when cameras share the same [`Camera::order`], the camera
+    /// with the closer hit is on top. Off by default, in which case the order is the camera's
+    /// order, as with other backends.
+    ///
+    /// The order is lowered by less than `0.5`, so this never moves hits below those of a camera
+    /// with a lower order, or below `bevy_ui`, which uses the camera order plus `0.5`.
+    pub order_by_depth: bool,
+    /// Added to the order of every [`PointerHits`] event sent by the rapier backend, to place its
+    /// hits deterministically above or below those of other backends using the same cameras. For
+    /// example, `bevy_ui` hits are at their camera's order plus `0.5`, so an offset of `1.0` puts
+    /// rapier hits above the UI of the same camera, and a small negative offset puts them below the
+    /// hits other backends make through the same camera.
+    /// Defaults to `0.0`, the camera's order, as with other backends.
+    pub order_offset: f32,
+    /// When set to `true`, each pointer only picks through the camera with the highest
+    /// [`Camera::order`] among the cameras it is over, such as a UI camera stacked on top of a
+    /// world camera. Off by default, in which case the pointer picks through every camera it is
+    /// over, and a [`PointerHits`] event is sent for each.
+    pub topmost_camera_only: bool,
+    /// When set to `true`, the hits of a pointer through all cameras with the same
+    /// [`Camera::order`] are merged into a single [`PointerHits`] event, sorted by depth. Off by
+    /// default, in which case an event is sent per camera, and the picking core can't tell which
+    /// of the events with the same order is on top.
+    ///
+    /// The merged hits go through [`RapierBackendSettings::hit_mode`],
+    /// [`RapierBackendSettings::selection`], and [`RapierBackendSettings::max_hits`] again, so that
+    /// e.g. only the globally closest hit is reported in [`HitMode::Closest`]. An entity hit
+    /// through several cameras is only reported once, at its nearest hit. Note that each depth is
+    /// measured from the camera the hit was made through.
+    pub merge_overlapping_cameras: bool,
+    /// The angle, in radians, within which the entity a pointer hit on the previous frame keeps
+    /// being reported when the pointer's ray now misses everything. This stops the hovered entity
+    /// from flickering when a pointer sits right on the edge of a collider. Defaults to `0.0`,
+    /// which disables this.
+    ///
+    /// The kept hit is reported at the point that was last actually hit, and is released as soon
+    /// as the ray hits anything or moves farther away than this angle.
+    pub hit_stickiness: f32,
+    /// When set to `true`, rays are also cast between where each pointer was on the previous frame
+    /// and where it is now, so a pointer that moves quickly still hits thin objects it passed over
+    /// in between. This is the picking analogue of continuous collision detection. When the ray
+    /// through the pointer misses, the hits of the swept ray nearest to the pointer's current
+    /// position are reported. Off by default.
+    ///
+    /// Swept rays are spaced two logical pixels apart, up to 32 of them per pointer and camera, and
+    /// are only cast while the ray through the pointer misses.
+    pub swept_pointer: bool,
+    /// When set to `true`, rays through cameras are cast backwards, from their maximum distance
+    /// towards the camera, so the first hit reported is the last surface along the ray. The
+    /// [`HitData::depth`] of hits is then measured from the far end of the ray. Off by default.
+    ///
+    /// This needs a finite [`RapierBackendSettings::max_ray_distance`], a
+    /// [`RapierPickableCamera::max_distance`] on the camera, or a finite far plane with
+    /// [`RapierBackendSettings::ray_distance_from_far_plane`]. Rays without one are cast forwards
+    /// as usual.
+    pub reverse_cast: bool,
+    /// When set to `true`, colliders whose bounding box is entirely outside of the view frustum of
+    /// the camera a ray is cast through are never picked, even if the ray hits them. This is a
+    /// guard against rays hitting things the camera can't see, such as colliders behind it with
+    /// unusual projections. Off by default.
+    ///
+    /// This uses the camera's [`Frustum`](bevy_render::primitives::Frustum) component, which is
+    /// kept up to date by Bevy for cameras spawned with a camera bundle. Cameras without one, and
+    /// [`RapierRayPointer`]s, aren't culled.
+    pub frustum_cull: bool,
+    /// When set to `true`, rays are cast against the colliders at their [`GlobalTransform`]s,
+    /// instead of where they were in rapier's last physics step. Off by default.
+    ///
+    /// With a fixed physics timestep and interpolated rendering, the physics positions lag behind
+    /// what is drawn, so picks can land slightly off fast moving objects. This is a best effort
+    /// fix: it tests every collider in turn rather than using rapier's acceleration structure, so
+    /// it is much slower in scenes with many colliders. It doesn't apply to
+    /// [`RapierBackendSettings::pick_shape`], and the [`RapierPointerHit::trimesh`] and
+    /// [`RapierPointerHit::compound_child`] are still computed against the physics positions.
+    pub use_interpolated_transforms: bool,
+    /// When set to `true`, rays are only cast again when they change, such as when the pointer or
+    /// camera moves. Otherwise the hits from the previous update are reported again, which saves
+    /// battery on mobile when the pointer sits still. Off by default.
+    ///
+    /// The cached hits are thrown away whenever the [`RapierContext`] or these settings change.
+    /// Rapier changes its context with every physics step, so this only helps while physics is
+    /// paused or not stepping. Changes to components, such as [`RenderLayers`] or [`Pickable`], are
+    /// not noticed until the cache is thrown away.
+    pub cache_static_pointers: bool,
+    /// When set, hits within this distance, in world units, of the nearest hit are considered tied,
+    /// and the tied entity with the highest [`RapierPickPriority`] is reported instead of the
+    /// nearest. `None` by default, in which case the nearest hit always wins. This is only used in
+    /// [`HitMode::Closest`] with [`HitSelection::Nearest`] or
+    /// [`HitSelection::LargestApparentSize`].
+    ///
+    /// Finding the tied hits costs another raycast per tied hit, plus one to find the end of the
+    /// tie, for every pointer that hits something.
+    pub priority_epsilon: Option<f32>,
+    /// When set to `true`, the entity with the lowest index, then generation, wins among hits at
+    /// exactly the same depth and [`RapierPickPriority`]. Otherwise the winner depends on rapier's
+    /// internal ordering, which isn't guaranteed to be stable, so this makes tests and replays that
+    /// depend on picking reproducible. Off by default. This is only used in the same hit modes as
+    /// [`RapierBackendSettings::priority_epsilon`].
+    ///
+    /// Finding the tied hits costs at least one more raycast for every pointer that hits something.
+    pub deterministic_ties: bool,
+}
+
+impl RapierBackendSettings {
+    /// Whether the [`RapierPointerHits`] are filled in and [`RapierPointerHit`]s are sent, because
+    /// they or any of their optional details were asked for.
+    fn provides_hit_details(&self) -> bool {
+        self.provide_hit_details
+            || self.provide_local_points
+            || self.provide_velocities
+            || self.provide_compound_children
+            || self.provide_trimesh_hits
+            || self.provide_smooth_normals
+            || self.provide_view_space_normals
+            || self.provide_ndc_depths
+            || self.provide_exit_points
+    }
+}
+
+impl Default for RapierBackendSettings {
+    fn default() -> Self {
+        Self {
+            require_markers: false,
+            enabled: true,
+            pointer_filter: PointerFilter::All,
+            hit_mode: HitMode::Closest,
+            selection: HitSelection::Nearest,
+            max_distinct_hits: None,
+            max_hits: 16,
+            max_ray_distance: None,
+            ray_distance_from_far_plane: false,
+            min_ray_distance: 0.0,
+            solid: true,
+            emit_empty_hits: false,
+            wait_for_async_colliders: false,
+            groups: None,
+            default_entity_layers: RenderLayers::default(),
+            default_camera_layers: RenderLayers::all(),
+            disabled_layers: RenderLayers::none(),
+            pick_disabled_colliders: false,
+            sensor_mode: SensorMode::Include,
+            ignore_mode: IgnoreMode::Transparent,
+            exclude_fixed: false,
+            exclude_dynamic: false,
+            exclude_kinematic: false,
+            pick_controller_shapes: false,
+            backface_culling: BackfaceMode::All,
+            max_backface_skips: 1,
+            ray_advance_bias: 1e-4,
+            pick_shape: None,
+            nearest_to_ray_tolerance: None,
+            samples: 1,
+            sample_radius: 4.0,
+            parallel: false,
+            order_by_depth: false,
+            order_offset: 0.0,
+            topmost_camera_only: false,
+            merge_overlapping_cameras: false,
+            provide_hit_details: false,
+            provide_local_points: false,
+            provide_velocities: false,
+            provide_compound_children: false,
+            provide_trimesh_hits: false,
+            provide_smooth_normals: false,
+            provide_view_space_normals: false,
+            compute_normals: true,
+            provide_ndc_depths: false,
+            provide_exit_points: false,
+            record_rays: false,
+            emit_max_distance_point: false,
+            log_ray_failures: false,
+            warn_on_misconfig: true,
+            hit_stickiness: 0.0,
+            swept_pointer: false,
+            reverse_cast: false,
+            frustum_cull: false,
+            use_interpolated_transforms: false,
+            cache_static_pointers: false,
+            priority_epsilon: None,
+            deterministic_ties: false,
+        }
+    }
+}
+
+/// Controls which pointers the rapier backend casts rays for.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Reflect)]
+#[reflect(Default)]
+pub enum PointerFilter {
+    /// Every pointer can pick rapier colliders.
+    #[default]
+    All,
+    /// Only the mouse pointer can pick rapier colliders. Use this to leave touch and custom
+    /// pointers to other backends, such as UI.
+    MouseOnly,
+    /// Only the listed pointers can pick rapier colliders.
+    Only(Vec<PointerId>),
+}
+
+impl PointerFilter {
+    /// Returns `true` if `pointer` can pick rapier colliders.
+    pub fn allows(&self, pointer: PointerId) -> bool {
+        match self {
+            PointerFilter::All => true,
+            PointerFilter::MouseOnly => pointer.is_mouse(),
+            PointerFilter::Only(pointers) => pointers.contains(&pointer),
+        }
+    }
+}
+
+/// Controls which colliders the rapier backend checks for backfaces.
+///
+/// A ray that hits the back of a surface, such as when it starts inside a hollow mesh, steps past
+/// it and is cast again, up to [`RapierBackendSettings::max_backface_skips`] times. Finding the
+/// surface normal to detect this has a cost for every hit, and for convex shapes and primitives,
+/// a ray can only hit a backface when it starts inside a hollow shape.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect)]
+#[reflect(Default)]
+pub enum BackfaceMode {
+    /// Every collider is checked for backfaces.
+    #[default]
+    All,
+    /// Only trimesh and heightfield colliders are checked for backfaces. Hits on other colliders
+    /// are always reported, saving the normal lookup and any extra raycast.
+    TrimeshOnly,
+    /// Backfaces are never skipped, and are reported like any other hit. Use this to pick the
+    /// inside surfaces of hollow meshes, such as with a camera inside an open box, or in cutaway
+    /// and section views.
+    Off,
+}
+
+/// Controls which of the entities along a ray are reported by the rapier backend.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect)]
+#[reflect(Default)]
+pub enum HitMode {
+    /// Only the closest entity is reported. This is the cheapest, needing a single raycast.
+    #[default]
+    Closest,
+    /// Every entity is reported, up to and including the first one that blocks lower entities, as
+    /// set by [`Pickable::should_block_lower`]. Entities without [`Pickable`] block. This matches
+    /// how layered UI backends behave, letting transparent colliders in front of a solid one emit
+    /// events.
+    ///
+    /// In 2d, where the entities under a pointer have no order, this is the same as
+    /// [`HitMode::All`].
+    UntilBlocking,
+    /// Every entity along the ray is reported.
+    All,
+}
+
+/// Controls whether the rapier backend selects the nearest or the farthest hit along a ray.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect)]
+#[reflect(Default)]
+pub enum HitSelection {
+    /// Hits are reported starting from the nearest one, as set by the [`HitMode`].
+    #[default]
+    Nearest,
+    /// Only the farthest hit is reported, the last collider the ray passes through before leaving
+    /// the scene or reaching its max distance. This is useful for "x-ray" selection. The ray walks
+    /// past every hit, with one raycast each, up to [`RapierBackendSettings::max_hits`], so the
+    /// [`HitMode`] is ignored.
+    Farthest,
+    /// Like [`HitSelection::Nearest`], but among hits within
+    /// [`RapierBackendSettings::priority_epsilon`] of the nearest one, the entity that looks the
+    /// largest from the camera is reported. This is useful to grab the big object when a pointer
+    /// is near several, such as in VR. The apparent size is the radius of the collider's bounding
+    /// sphere over its distance from the ray's origin, and is only compared between entities of
+    /// the same [`RapierPickPriority`].
+    ///
+    /// Without a `priority_epsilon`, or outside of [`HitMode::Closest`], this is the same as
+    /// [`HitSelection::Nearest`]. This is only used by the 3d backend.
+    LargestApparentSize,
+}
+
+/// Controls how sensor colliders, also known as triggers, are treated by the rapier backend.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect)]
+#[reflect(Default)]
+pub enum SensorMode {
+    /// Sensors are picked like any other collider.
+    #[default]
+    Include,
+    /// Sensors are never picked. Use this to prevent invisible detection volumes from blocking
+    /// picks on the entities behind them.
+    Exclude,
+    /// Only sensors are picked, solid colliders are ignored.
+    Only,
+}
+
+/// Controls how the rapier backend treats entities with [`Pickable::IGNORE`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect)]
+#[reflect(Default)]
+pub enum IgnoreMode {
+    /// Ignored entities are left out of the raycast entirely, so rays pass through them and hit
+    /// whatever is behind them.
+    #[default]
+    Transparent,
+    /// Ignored entities are hit like any other entity, but are never reported, and nothing behind
+    /// them is reported either. This gives depth-accurate occlusion, e.g. for selection outlines
+    /// that shouldn't show through walls the pointer can't interact with.
+    ///
+    /// In 2d, where no entity is in front of another, this is the same as
+    /// [`IgnoreMode::Transparent`].
+    Occluding,
 }
 
 /// Optional. Marks cameras and target entities that should be used in the rapier picking backend.
@@ -71,107 +845,1327 @@ pub struct RapierBackendSettings {
 #[reflect(Component, Default)]
 pub struct RapierPickable;
 
+/// Optional. Marks an entity that can never be picked by the rapier backend, no matter the other
+/// settings. Unlike [`Pickable::IGNORE`], this only affects the rapier backend, and has no effect on
+/// how other backends block or report hits. The entity can still be rendered and collide.
+#[derive(Debug, Clone, Default, Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct RapierUnpickable;
+
+/// Optional. Marks an entity that pick rays pass through, such as glass or a trigger volume drawn in
+/// front of the scene. The walk along the ray continues past the entity, as with [`HitMode::All`],
+/// but it is never reported, and the first hit behind it is reported instead. It does not count
+/// towards [`RapierBackendSettings::max_hits`].
+///
+/// Unlike [`RapierUnpickable`] or [`Pickable::IGNORE`], which remove the entity from the cast
+/// entirely, the collider is still hit and stepped over. In the 2d backend there is nothing behind a
+/// point to step over, so this behaves like [`RapierUnpickable`].
+#[derive(Debug, Clone, Default, Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct RapierPickTransparent;
+
+/// Optional. The priority of an entity when it is hit at nearly the same depth as others, such as
+/// coplanar decals. Among hits within [`RapierBackendSettings::priority_epsilon`] of the nearest
+/// one, the entity with the highest priority is reported. Entities without this component have a
+/// priority of `0`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct RapierPickPriority(pub i32);
+
+/// Optional. A distance, in world units, added to the depth of every hit on the entity this is
+/// added to, before the hits are ordered and reported in [`HitData::depth`]. A positive bias pushes
+/// the entity farther away, and a negative one pulls it closer, so coplanar surfaces win or lose
+/// predictably. This is only used by the 3d backend.
+///
+/// The bias only reorders hits that were found. In [`HitMode::Closest`], rays stop at the first
+/// hit, so use it with [`RapierBackendSettings::priority_epsilon`] to let entities behind the
+/// nearest one compete.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct RapierDepthBias(pub f32);
+
+/// Optional. Per-camera settings for the [`RapierBackend`] that override the global
+/// [`RapierBackendSettings`] for rays cast from the camera this is added to.
+#[derive(Debug, Clone, Default, Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct RapierPickableCamera {
+    /// Overrides [`RapierBackendSettings::max_ray_distance`] for this camera. When `None`, the
+    /// global setting is used.
+    pub max_distance: Option<f32>,
+    /// When set to `true`, pointers are picked through this camera even while it isn't active.
+    /// This lets a dedicated picking camera avoid the cost of rendering. Off by default, in which
+    /// case inactive cameras are skipped.
+    pub pick_while_inactive: bool,
+}
+
+impl RapierPickableCamera {
+    /// Returns `true` if pointers should be picked through `camera`, which has `cam_settings`.
+    pub(crate) fn allows(cam_settings: Option<&Self>, camera: &Camera) -> bool {
+        camera.is_active
+            || cam_settings.is_some_and(|cam_settings| cam_settings.pick_while_inactive)
+    }
+}
+
+/// A pointer that casts a ray straight into the world, instead of through a camera, such as a
+/// tracked VR controller's laser pointer. Add this to an entity with a [`PointerId`], and update
+/// it from the controller's transform; the [`RapierBackend`] casts the ray with the same filtering
+/// and backface handling as rays through cameras. This doesn't need a [`PointerLocation`].
+///
+/// The hits are reported with this entity as their [`HitData::camera`], and at order `0`. Rays
+/// from this pointer are on the [`RapierBackendSettings::default_camera_layers`], and use the
+/// global [`RapierBackendSettings::max_ray_distance`]. This is only used by the 3d backend.
+#[derive(Debug, Clone, Copy, PartialEq, Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct RapierRayPointer {
+    /// Where the ray starts, in world space.
+    pub origin: Vec3,
+    /// The direction the ray points in, in world space. This doesn't need to be normalized, but a
+    /// zero direction casts no ray.
+    pub direction: Vec3,
+}
+
+impl Default for RapierRayPointer {
+    fn default() -> Self {
+        Self {
+            origin: Vec3::ZERO,
+            direction: Vec3::NEG_Z,
+        }
+    }
+}
+
+/// Optional. Reports the hits of the pointer this is added to as if they were made through the
+/// given camera, while still casting through the cameras the pointer is over. For example, in a
+/// picture-in-picture setup, picks through the inset camera can be ordered as if they were made
+/// through the main camera.
+///
+/// The hits use this camera as their [`HitData::camera`], and its [`Camera::order`] as the order
+/// of their [`PointerHits`]. This is ignored while the entity isn't a camera. The rapier specific
+/// details, such as [`RapierPickRays`] and [`RapierPointerHit::camera`], still refer to the camera
+/// the ray was cast through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component, Reflect)]
+#[reflect(Component)]
+pub struct RapierOrderCamera(pub Entity);
+
+impl FromWorld for RapierOrderCamera {
+    fn from_world(_world: &mut World) -> Self {
+        Self(Entity::PLACEHOLDER)
+    }
+}
+
+/// Optional. Changes where the rays cast from the camera this is added to start and point, for
+/// example to make picks come out of a weapon's muzzle in a first-person game. The ray is first
+/// computed from the pointer's position as usual, then adjusted by this component. This is only
+/// used by the 3d backend.
+#[derive(Debug, Clone, Default, Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct RapierPickRayOverride {
+    /// Moves the origin of the ray by this offset, in the camera's local space.
+    pub origin_offset: Vec3,
+    /// When set, the ray points in this direction, in the camera's local space, instead of through
+    /// the pointer. When `None` or zero, the direction through the pointer is kept.
+    pub direction: Option<Vec3>,
+}
+
+/// Optional. Entities that rays cast from the camera this is added to pass straight through,
+/// such as the player's own body when the camera is attached to it. Listing a rigid body excludes
+/// every collider attached to it.
+///
+/// Unlike [`RapierUnpickable`] or [`Pickable::IGNORE`], this only applies to this camera. Excluded
+/// colliders are skipped on top of all other filtering: they are never hit, so they can't block
+/// the colliders behind them either. This is only used by the 3d backend.
+#[derive(Debug, Clone, Default, Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct RapierPickExclude(pub Vec<Entity>);
+
+/// Optional. Restricts rays cast from the camera this is added to, to colliders that are a member
+/// of any of these groups, according to their [`CollisionGroups::memberships`]. This lets one
+/// camera pick, for example, interactables or enemies depending on the game's mode. Colliders
+/// without [`CollisionGroups`] are members of every group, as they are in rapier. An empty list
+/// picks nothing.
+///
+/// Unlike [`RapierBackendSettings::groups`], which rapier checks itself, this is checked in the
+/// predicate called for every candidate collider, adding a component lookup and a check of each
+/// group to every candidate. This is only used by the 3d backend.
+#[derive(Debug, Clone, Default, Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct RapierPickGroups(pub Vec<Group>);
+
+/// Optional. A point, in the local space of the entity this is added to, that is reported as the
+/// [`HitData::position`] of every hit on the entity instead of the point on its surface that was
+/// hit. This lets a complex prop be grabbed by a designer-authored handle without changing its
+/// collider. The surface point is still available in [`RapierPointerHit::surface_point`], and the
+/// depth and normal of the hit are unchanged.
+///
+/// This is only used by the hits reported by the 3d backend, not by [`RapierPicker::pick_at`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct RapierPickAnchor(pub Vec3);
+
+impl RapierPickRayOverride {
+    /// Applies this override to a `ray` cast from a camera with the given transform.
+    pub fn apply(&self, ray: Ray, camera_transform: &GlobalTransform) -> Ray {
+        let (_, rotation, _) = camera_transform.to_scale_rotation_translation();
+        Ray {
+            origin: ray.origin + rotation * self.origin_offset,
+            direction: self
+                .direction
+                .map(|direction| (rotation * direction).normalize_or_zero())
+                .filter(|direction| *direction != Vec3::ZERO)
+                .unwrap_or(ray.direction),
+        }
+    }
+}
+
+/// An optional, user-supplied filter that is checked for every collider a pick ray could hit, in
+/// addition to the backend's own checks such as [`RapierBackendSettings::require_markers`] and
+/// render layers. Entities the closure returns `false` for can't be picked.
+///
+/// This is an escape hatch for filtering on gameplay state the backend doesn't know about. The
+/// closure only receives the [`Entity`], so any data it needs must be captured, for example by
+/// replacing this resource from one of your systems when that data changes.
+///
+/// The closure is run by rapier for each candidate collider the ray passes through on every
+/// raycast, so it should be cheap. If all you need is to ignore an entity, prefer adding
+/// [`Pickable::IGNORE`] to it.
+#[derive(Resource)]
+pub struct RapierPickFilter(pub Box<dyn Fn(Entity) -> bool + Send + Sync>);
+
+impl RapierPickFilter {
+    /// Creates a filter from a closure.
+    pub fn new(filter: impl Fn(Entity) -> bool + Send + Sync + 'static) -> Self {
+        Self(Box::new(filter))
+    }
+
+    /// Returns `true` if the filter allows `entity` to be picked.
+    pub fn allows(&self, entity: Entity) -> bool {
+        (self.0)(entity)
+    }
+}
+
+/// A rule that decides whether an entity can be picked by the rapier backends, with read access to
+/// the whole [`World`]. Closures taking the entity and the world are rules too. Add rules to the
+/// world in [`RapierPickRule`] components.
+pub trait PickRule: Send + Sync + 'static {
+    /// Returns `true` if `entity`, which has a collider, can be picked.
+    fn allows(&self, entity: Entity, world: &World) -> bool;
+}
+
+impl<F: Fn(Entity, &World) -> bool + Send + Sync + 'static> PickRule for F {
+    fn allows(&self, entity: Entity, world: &World) -> bool {
+        self(entity, world)
+    }
+}
+
+/// A [`PickRule`] checked by the rapier backends. Rules can be added to any entity, and an entity
+/// with a collider can only be picked if every rule in the world allows it, on top of the
+/// [`RapierPickFilter`] and the backend's own checks. Unlike a [`RapierPickFilter`], a rule doesn't
+/// need to capture the data it checks, and several plugins can each spawn their own rules.
+///
+/// Rules are evaluated by [`apply_pick_rules`] once per frame for every entity with a collider,
+/// before any rays are cast, and not at all while there are none. They are evaluated in no
+/// particular order, and evaluation of an entity stops at the first rule that denies it, so rules
+/// shouldn't rely on each other. The cost grows with the number of colliders and rules, no matter
+/// how many colliders are under a pointer, so prefer a [`RapierPickFilter`] for checks that only
+/// need the entity, as it is only run for candidates.
+#[derive(Component)]
+pub struct RapierPickRule(Box<dyn PickRule>);
+
+impl RapierPickRule {
+    /// Creates a component checking `rule`.
+    pub fn new(rule: impl PickRule) -> Self {
+        Self(Box::new(rule))
+    }
+
+    /// Returns `true` if the rule allows `entity` to be picked.
+    pub fn allows(&self, entity: Entity, world: &World) -> bool {
+        self.0.allows(entity, world)
+    }
+}
+
+/// The entities with a collider of type `C` denied by a [`RapierPickRule`] when the rules were
+/// last evaluated by [`apply_pick_rules`]. Each backend has its own results, as they pick
+/// different colliders.
+#[derive(Resource)]
+pub struct RapierPickRuleResults<C: Component> {
+    denied: HashSet<Entity>,
+    collider: PhantomData<fn() -> C>,
+}
+
+impl<C: Component> Default for RapierPickRuleResults<C> {
+    fn default() -> Self {
+        Self {
+            denied: HashSet::default(),
+            collider: PhantomData,
+        }
+    }
+}
+
+impl<C: Component> RapierPickRuleResults<C> {
+    /// Returns `true` if every rule allowed `entity` when they were last evaluated.
+    pub fn allows(&self, entity: Entity) -> bool {
+        !self.denied.contains(&entity)
+    }
+}
+
+/// Evaluates every [`RapierPickRule`] for every entity with a collider of type `C`, storing the
+/// results in [`RapierPickRuleResults`] so the backends can check them while casting rays.
+pub fn apply_pick_rules<C: Component>(
+    world: &mut World,
+    rules: &mut QueryState<&RapierPickRule>,
+    colliders: &mut QueryState<Entity, With<C>>,
+) {
+    let rules: Vec<&RapierPickRule> = rules.iter(world).collect();
+    let denied = colliders
+        .iter(world)
+        .filter(|entity| !rules.iter().all(|rule| rule.allows(*entity, world)))
+        .collect();
+    world.resource_mut::<RapierPickRuleResults<C>>().denied = denied;
+}
+
+/// Whether [`apply_pick_rules`] needs to run: while there are rules, and once more after the last
+/// one is removed, to clear the entities it denied.
+pub(crate) fn has_pick_rules<C: Component>(
+    rules: Query<(), With<RapierPickRule>>,
+    results: Res<RapierPickRuleResults<C>>,
+) -> bool {
+    !rules.is_empty() || !results.denied.is_empty()
+}
+
+/// The rapier specific details of a hit reported by the [`RapierBackend`], on top of its
+/// [`HitData`]. This can be ignored unless you need rapier's internals. When
+/// [`RapierBackendSettings::provide_hit_details`] is enabled, it is sent as an event alongside
+/// [`PointerHits`] for every hit, and the hits of the latest update are kept in the
+/// [`RapierPointerHits`] resource, so they can be looked up without reading the events. The
+/// optional details are each enabled by their own setting, as they cost more to find.
+///
+/// Hits on the [`KinematicCharacterController::custom_shape`] of a character controller are not on
+/// a collider, so they have no details.
+#[derive(Debug, Clone, Event)]
+pub struct RapierPointerHit {
+    /// The pointer that made the hit.
+    pub pointer: PointerId,
+    /// The camera the hit was made through.
+    pub camera: Entity,
+    /// The entity that was hit.
+    pub entity: Entity,
+    /// The handle of the collider that was hit. This saves looking the handle up in the
+    /// [`RapierContext`] when you keep your own data keyed by collider handle.
+    pub handle: ColliderHandle,
+    /// The rapier feature of the collider that was hit. For trimeshes, this identifies the exact
+    /// triangle that was hit, which can be used for face-level selection.
+    pub feature: FeatureId,
+    /// Whether the collider that was hit is a sensor. This lets sensors included by
+    /// [`SensorMode::Include`] be handled differently from solid colliders, e.g. as triggers.
+    pub is_sensor: bool,
+    /// The time of impact reported by rapier. This is measured from where the last trace along the
+    /// ray started, which is not the ray's origin if backfaces or previous hits were skipped.
+    pub toi: f32,
+    /// The distance along the ray from its origin to the hit, as in [`HitData::depth`].
+    pub depth: f32,
+    /// The position of the hit in the local space of the entity that was hit. This is the
+    /// [`HitData::position`] transformed by the inverse of the entity's [`GlobalTransform`], which
+    /// is useful to e.g. map hits onto a texture. For entities with a [`RapierPickAnchor`], this is
+    /// the point on the surface that was hit, not the anchor. Only provided when
+    /// [`RapierBackendSettings::provide_local_points`] is enabled.
+    pub local_point: Option<Vec3>,
+    /// The world space point on the surface that was hit, when the [`HitData::position`] was
+    /// replaced by a [`RapierPickAnchor`]. `None` for entities without an anchor, as their position
+    /// already is the surface point.
+    pub surface_point: Option<Vec3>,
+    /// The velocity, in world units per second, of the collider at the point that was hit. This
+    /// includes both the linear and angular velocity of the collider's rigid body, as known to
+    /// rapier, for example to lead a target or to throw what is picked along with its motion.
+    /// Colliders on fixed bodies, and colliders without a rigid body, have a velocity of zero. Only
+    /// provided when [`RapierBackendSettings::provide_velocities`] is enabled.
+    pub velocity: Option<Vec3>,
+    /// The index of the child shape that was hit, for hits on compound colliders. The index is into
+    /// the compound's list of shapes, in the order they were given to [`Collider::compound`]. Only
+    /// provided when [`RapierBackendSettings::provide_compound_children`] is enabled.
+    pub compound_child: Option<usize>,
+    /// Where the ray hit the triangle of a trimesh collider, when
+    /// [`RapierBackendSettings::provide_trimesh_hits`] is enabled. `None` for other shapes.
+    pub trimesh: Option<TrimeshHit>,
+    /// The world space normal interpolated from the vertex normals of the triangle that was hit on
+    /// a trimesh collider. This points to the same side of the surface as the flat triangle normal
+    /// in the hit's [`HitData::normal`]. Only provided when
+    /// [`RapierBackendSettings::provide_smooth_normals`] is enabled.
+    ///
+    /// This is useful for smooth shaded meshes, for example to orient a decal with the smooth
+    /// normal while offsetting it along the flat one. Rapier only has vertex normals for trimeshes
+    /// built with [`TriMeshFlags::ORIENTED`], which computes them from the angles of the
+    /// surrounding triangles, so this is `None` for other trimeshes and shapes.
+    pub smooth_normal: Option<Vec3>,
+    /// The normal of the hit in the view space of the camera it was hit through, when
+    /// [`RapierBackendSettings::provide_view_space_normals`] is enabled. The world space normal is
+    /// still reported in [`HitData::normal`].
+    pub view_normal: Option<Vec3>,
+    /// The depth of the hit in the normalized device coordinates of the camera it was hit through,
+    /// when [`RapierBackendSettings::provide_ndc_depths`] is enabled. The world space depth is still
+    /// reported in [`HitData::depth`].
+    ///
+    /// This is the value the GPU writes to the depth buffer: Bevy uses a reversed depth, so it goes
+    /// from `1` at the camera's near plane towards `0` in the distance. Hits on
+    /// [`RapierRayPointer`]s aren't made through a camera, so they have no depth.
+    pub ndc_depth: Option<f32>,
+    /// Where the ray entered and left the collider, when
+    /// [`RapierBackendSettings::provide_exit_points`] is enabled.
+    ///
+    /// The exit is the first surface of the same collider the ray reaches after entering it, so for
+    /// shapes with holes or cavities, this is where the ray first leaves the solid part. Hits on
+    /// colliders that aren't closed, such as a single plane or an open trimesh, may have no exit.
+    pub span: Option<RapierHitSpan>,
+}
+
+/// The [`RapierPointerHit`] of every hit reported by the [`RapierBackend`] in the latest update,
+/// keyed by the pointer and the entity that was hit.
+///
+/// If the same entity is hit by a pointer through multiple cameras, the last hit is stored.
+#[derive(Debug, Default, Clone, Resource)]
+pub struct RapierPointerHits(pub HashMap<(PointerId, Entity), RapierPointerHit>);
+
+impl RapierPointerHits {
+    /// Returns the details of the hit `pointer` made on `entity`, if any.
+    pub fn get(&self, pointer: PointerId, entity: Entity) -> Option<&RapierPointerHit> {
+        self.0.get(&(pointer, entity))
+    }
+}
+
+/// The rapier [`FeatureId`] of every hit is now in [`RapierPointerHit::feature`].
+#[deprecated(note = "use `RapierPointerHits`, and `RapierPointerHit::feature`")]
+pub type RapierHitFeatures = RapierPointerHits;
+
+/// The rapier [`ColliderHandle`] of every hit is now in [`RapierPointerHit::handle`].
+#[deprecated(note = "use `RapierPointerHits`, and `RapierPointerHit::handle`")]
+pub type RapierPickHandles = RapierPointerHits;
+
+/// The triangle and barycentric coordinates of every trimesh hit are now in
+/// [`RapierPointerHit::trimesh`].
+#[deprecated(note = "use `RapierPointerHits`, and `RapierPointerHit::trimesh`")]
+pub type RapierPickUvs = RapierPointerHits;
+
+/// The position of every hit in the local space of the entity hit is now in
+/// [`RapierPointerHit::local_point`].
+#[deprecated(note = "use `RapierPointerHits`, and `RapierPointerHit::local_point`")]
+pub type RapierLocalHits = RapierPointerHits;
+
+/// The velocity of the collider at every hit is now in [`RapierPointerHit::velocity`].
+#[deprecated(note = "use `RapierPointerHits`, and `RapierPointerHit::velocity`")]
+pub type RapierHitVelocities = RapierPointerHits;
+
+/// The ray cast by the [`RapierBackend`] for every pointer and camera in the latest update, keyed
+/// by the pointer and the camera entity. This is only filled in when
+/// [`RapierBackendSettings::record_rays`] is enabled.
+///
+/// These are the exact rays used for picking, including any [`RapierPickRayOverride`], so effects
+/// such as outlines or projectiles can trace the same ray that decided what was picked.
+#[derive(Debug, Default, Clone, Resource)]
+pub struct RapierPickRays(pub HashMap<(PointerId, Entity), Ray>);
+
+impl RapierPickRays {
+    /// Returns the ray cast for `pointer` through `camera`, if any.
+    pub fn get(&self, pointer: PointerId, camera: Entity) -> Option<Ray> {
+        self.0.get(&(pointer, camera)).copied()
+    }
+}
+
+/// Where a ray entered and left a collider. See [`RapierPointerHit::span`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RapierHitSpan {
+    /// The world space point where the ray entered the collider, the same as the hit's position.
+    pub entry: Vec3,
+    /// The world space point where the ray left the collider, past the entry.
+    pub exit: Vec3,
+}
+
+impl RapierHitSpan {
+    /// The distance the ray travelled inside the collider.
+    pub fn thickness(&self) -> f32 {
+        self.entry.distance(self.exit)
+    }
+}
+
+/// The point at the end of a ray that didn't hit anything. See [`RapierMissPoints`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RapierMissPoint {
+    /// The world space point at the maximum distance along the ray. This is the far end of the ray
+    /// even when it is cast towards the camera with [`RapierBackendSettings::reverse_cast`].
+    pub point: Vec3,
+    /// The normalized direction of the ray through the camera, which points towards the
+    /// [`point`](Self::point).
+    pub direction: Vec3,
+}
+
+/// The point at the maximum distance along every ray cast by the [`RapierBackend`] in the latest
+/// update that didn't hit anything, keyed by the pointer and the camera entity. This is only filled
+/// in when [`RapierBackendSettings::emit_max_distance_point`] is enabled, and only for rays with a
+/// maximum distance, from [`RapierBackendSettings::max_ray_distance`] or
+/// [`RapierPickableCamera::max_distance`].
+///
+/// This is useful for placement tools, e.g. to place a marker in empty space where nothing was hit.
+#[derive(Debug, Default, Clone, Resource)]
+pub struct RapierMissPoints(pub HashMap<(PointerId, Entity), RapierMissPoint>);
+
+impl RapierMissPoints {
+    /// Returns the end of the ray cast for `pointer` through `camera`, if it missed.
+    pub fn get(&self, pointer: PointerId, camera: Entity) -> Option<RapierMissPoint> {
+        self.0.get(&(pointer, camera)).copied()
+    }
+}
+
+/// Where a ray hit a triangle of a trimesh collider. See [`RapierPointerHit::trimesh`].
+///
+/// Rapier doesn't know about texture coordinates, but if the trimesh was built from a mesh, you can
+/// find the UV of a hit by interpolating the UVs of the triangle's vertices with the barycentric
+/// coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrimeshHit {
+    /// The index of the triangle that was hit, in the trimesh's list of triangles.
+    pub triangle_index: u32,
+    /// The barycentric coordinates of the hit within the triangle. Each component is the weight of
+    /// the corresponding triangle vertex, in order, and the components sum to one.
+    pub barycentric: Vec3,
+    /// The world space positions of the vertices of the triangle, in the same order as the
+    /// barycentric coordinates. This is useful to snap to the exact triangle under a pointer,
+    /// without looking up the trimesh.
+    pub vertices: [Vec3; 3],
+}
+
+/// Checks the [`RapierBackendSettings`] whenever they change, such as when they are edited live in
+/// an inspector. Invalid values are logged, and replaced with the closest valid value.
+pub fn validate_settings(mut settings: ResMut<RapierBackendSettings>) {
+    if !settings.is_changed() {
+        return;
+    }
+    if settings.max_hits == 0 {
+        warn!("`RapierBackendSettings::max_hits` must be at least 1, using 1 instead.");
+        settings.max_hits = 1;
+    }
+    if settings.max_distinct_hits == Some(0) {
+        warn!("`RapierBackendSettings::max_distinct_hits` must be at least 1, using 1 instead.");
+        settings.max_distinct_hits = Some(1);
+    }
+    if let Some(distance) = settings.max_ray_distance {
+        if distance.is_nan() || distance <= 0.0 {
+            warn!(
+                "`RapierBackendSettings::max_ray_distance` must be positive, but is {distance}. \
+                Rays will be unbounded instead."
+            );
+            settings.max_ray_distance = None;
+        }
+    }
+    if let Some(tolerance) = settings.nearest_to_ray_tolerance {
+        if !tolerance.is_finite() || tolerance <= 0.0 {
+            warn!(
+                "`RapierBackendSettings::nearest_to_ray_tolerance` must be positive and finite, but \
+                is {tolerance}. Picks will only use the ray instead."
+            );
+            settings.nearest_to_ray_tolerance = None;
+        }
+    }
+    if !settings.order_offset.is_finite() {
+        warn!(
+            "`RapierBackendSettings::order_offset` must be finite, but is {}. Using 0 instead.",
+            settings.order_offset
+        );
+        settings.order_offset = 0.0;
+    }
+    let min_distance = settings.min_ray_distance;
+    if min_distance.is_nan() || min_distance < 0.0 {
+        warn!(
+            "`RapierBackendSettings::min_ray_distance` can't be negative, but is {min_distance}. \
+            Using 0 instead."
+        );
+        settings.min_ray_distance = 0.0;
+    }
+    let bias = settings.ray_advance_bias;
+    if bias.is_nan() || bias < 0.0 {
+        let default = RapierBackendSettings::default().ray_advance_bias;
+        warn!(
+            "`RapierBackendSettings::ray_advance_bias` can't be negative, but is {bias}. \
+            Using {default} instead."
+        );
+        settings.ray_advance_bias = default;
+    }
+    let stickiness = settings.hit_stickiness;
+    if stickiness.is_nan() || stickiness < 0.0 {
+        warn!(
+            "`RapierBackendSettings::hit_stickiness` can't be negative, but is {stickiness}. \
+            Using 0 instead."
+        );
+        settings.hit_stickiness = 0.0;
+    }
+    let radius = settings.sample_radius;
+    if !radius.is_finite() || radius < 0.0 {
+        let default = RapierBackendSettings::default().sample_radius;
+        warn!(
+            "`RapierBackendSettings::sample_radius` must be finite and not negative, but is \
+            {radius}. Using {default} instead."
+        );
+        settings.sample_radius = default;
+    }
+    if let Some(epsilon) = settings.priority_epsilon {
+        if epsilon.is_nan() || epsilon < 0.0 {
+            warn!(
+                "`RapierBackendSettings::priority_epsilon` can't be negative, but is {epsilon}. \
+                Using 0 instead."
+            );
+            settings.priority_epsilon = Some(0.0);
+        }
+    }
+}
+
+/// Logs a warning, once, when [`RapierBackendSettings::require_markers`] is set but no camera has a
+/// [`RapierPickable`] marker, so nothing can be picked. Enabled by
+/// [`RapierBackendSettings::warn_on_misconfig`].
+pub fn warn_unmarked_cameras(
+    settings: Res<RapierBackendSettings>,
+    cameras: Query<(), With<Camera>>,
+    marked_cameras: Query<(), (With<Camera>, With<RapierPickable>)>,
+    mut warned: Local<bool>,
+) {
+    if *warned || !settings.warn_on_misconfig || !settings.require_markers {
+        return;
+    }
+    if !cameras.is_empty() && marked_cameras.is_empty() {
+        warn!(
+            "`RapierBackendSettings::require_markers` is set, but no camera has a `RapierPickable` \
+            marker, so nothing can be picked. Add `RapierPickable` to the cameras to pick through."
+        );
+        *warned = true;
+    }
+}
+
+/// The entities whose colliders `bevy_rapier` is still building from a mesh or scene, with an
+/// [`AsyncCollider`] or [`AsyncSceneCollider`], as of the latest update. These have no collider
+/// yet, so they can't be picked, and rays pass through them silently. Tools can check this to tell
+/// an entity that isn't ready yet apart from one that was missed, and wait for it. This is only
+/// filled in by the 3d backend.
+#[derive(Debug, Default, Clone, Resource)]
+pub struct RapierPendingColliders(pub HashSet<Entity>);
+
+impl RapierPendingColliders {
+    /// Returns `true` if the collider of `entity` is still being built.
+    pub fn is_pending(&self, entity: Entity) -> bool {
+        self.0.contains(&entity)
+    }
+}
+
+/// Updates the [`RapierPendingColliders`] with every entity still waiting for its collider.
+pub fn track_pending_colliders(
+    mut pending: ResMut<RapierPendingColliders>,
+    async_colliders: Query<Entity, Or<(With<AsyncCollider>, With<AsyncSceneCollider>)>>,
+) {
+    if pending.0.is_empty() && async_colliders.is_empty() {
+        return;
+    }
+    pending.0.clear();
+    pending.0.extend(async_colliders.iter());
+}
+
+/// The entity each pointer is over according to the [`RapierBackend`] in the latest update, for
+/// systems that only need to know what is under a pointer without reading [`PointerHits`]. This is
+/// the nearest hit of the pointer's [`PointerHits`] with the highest order, and `None` for pointers
+/// that didn't hit anything, including while the backend is disabled.
+///
+/// This only reflects rapier hits, not those of other backends, or whether another backend's hits
+/// are in front of them. Pointers picking through the 2d backend are not included.
+#[derive(Debug, Default, Clone, Resource)]
+pub struct RapierHovered(pub HashMap<PointerId, Option<Entity>>);
+
+impl RapierHovered {
+    /// Returns the entity `pointer` is over, or `None` if it isn't over any.
+    pub fn get(&self, pointer: PointerId) -> Option<Entity> {
+        self.0.get(&pointer).copied().flatten()
+    }
+}
+
+/// The resources [`update_hits`] fills in with the details of each hit, besides [`PointerHits`].
+#[derive(SystemParam)]
+pub struct RapierHitDetails<'w> {
+    hits: ResMut<'w, RapierPointerHits>,
+    rays: ResMut<'w, RapierPickRays>,
+    misses: ResMut<'w, RapierMissPoints>,
+    hovered: ResMut<'w, RapierHovered>,
+}
+
+impl RapierHitDetails<'_> {
+    /// Clears the details of the previous update.
+    fn clear(&mut self) {
+        self.hits.0.clear();
+        self.rays.0.clear();
+        self.misses.0.clear();
+        self.hovered.0.clear();
+    }
+}
+
 /// Raycasts into the scene using [`RapierBackendSettings`] and [`PointerLocation`]s, then outputs
 /// [`PointerHits`].
 pub fn update_hits(
-    pointers: Query<(&PointerId, &PointerLocation)>,
+    pointers: Query<(&PointerId, &PointerLocation, Option<&RapierOrderCamera>)>,
     primary_window_entity: Query<Entity, With<PrimaryWindow>>,
-    picking_cameras: Query<(
+    picker: RapierPicker,
+    mut details: RapierHitDetails,
+    anchors: Query<&RapierPickAnchor>,
+    ray_pointers: Query<(
         Entity,
-        &Camera,
-        &GlobalTransform,
-        Option<&RapierPickable>,
-        Option<&RenderLayers>,
+        &PointerId,
+        &RapierRayPointer,
+        Option<&RapierOrderCamera>,
     )>,
-    pickables: Query<&Pickable>,
-    marked_targets: Query<&RapierPickable>,
-    layers: Query<&RenderLayers>,
-    backend_settings: Res<RapierBackendSettings>,
-    q_colliders: Query<(&Collider, &GlobalTransform)>,
-    rapier_context: Option<Res<RapierContext>>,
+    mut last_hits: Local<HashMap<(PointerId, Entity), (Entity, RayIntersection)>>,
+    mut failed_cameras: Local<HashSet<Entity>>,
+    mut was_disabled: Local<bool>,
+    mut last_positions: Local<HashMap<(PointerId, Entity), Vec2>>,
+    mut cached_hits: Local<
+        HashMap<(PointerId, Entity), (Ray, Vec<(Entity, f32, RayIntersection)>)>,
+    >,
+    #[cfg(feature = "debug")] mut debug_rays: Option<ResMut<debug::RapierDebugRays>>,
+    #[cfg(feature = "diagnostics")] mut pick_counts: Option<ResMut<diagnostics::RapierPickCounts>>,
+    pending_colliders: Res<RapierPendingColliders>,
+    mut rapier_hits: EventWriter<RapierPointerHit>,
     mut output_events: EventWriter<PointerHits>,
 ) {
-    let Some(rapier_context) = rapier_context else {
+    details.clear();
+    #[cfg(feature = "debug")]
+    if let Some(debug_rays) = debug_rays.as_mut() {
+        debug_rays.0.clear();
+    }
+    #[cfg(feature = "diagnostics")]
+    if let Some(pick_counts) = pick_counts.as_mut() {
+        **pick_counts = diagnostics::RapierPickCounts::default();
+    }
+
+    let backend_settings = &*picker.backend_settings;
+    // Every pointer is over nothing until one of its hits says otherwise.
+    let all_pointers = pointers
+        .iter()
+        .map(|(pointer_id, ..)| *pointer_id)
+        .chain(ray_pointers.iter().map(|(_, pointer_id, ..)| *pointer_id))
+        .filter(|pointer_id| backend_settings.pointer_filter.allows(*pointer_id));
+    details
+        .hovered
+        .0
+        .extend(all_pointers.map(|pointer_id| (pointer_id, None)));
+    // While disabled, empty hits are still sent on the first update, to clear any hovers, but
+    // nothing is sent after that, even with `emit_empty_hits`.
+    let just_disabled = !backend_settings.enabled && !std::mem::replace(&mut *was_disabled, true);
+    if backend_settings.enabled {
+        *was_disabled = false;
+    } else if !just_disabled {
         return;
-    };
+    }
+    // Skip all work while disabled, while physics is still loading, or in physics-heavy scenes
+    // where nothing can be picked, unless empty hits still need to be reported.
+    let nothing_pickable =
+        !backend_settings.enabled || picker.rapier_context.is_none() || picker.nothing_pickable();
+    // Misses aren't reported while they may only be due to colliders that aren't built yet.
+    let emit_empty_hits = backend_settings.emit_empty_hits
+        && (!backend_settings.wait_for_async_colliders || pending_colliders.0.is_empty());
+    if nothing_pickable && !emit_empty_hits && !just_disabled {
+        return;
+    }
 
-    for (pointer_id, pointer_location) in &pointers {
-        let pointer_location = match pointer_location.location() {
-            Some(l) => l,
-            None => continue,
+    let primary_window = primary_window_entity.get_single().ok();
+    let order_as = |pick_ray: &mut PickRay, order_camera: Option<&RapierOrderCamera>| {
+        if let Some((entity, camera, ..)) =
+            order_camera.and_then(|order_camera| picker.cameras.get(order_camera.0).ok())
+        {
+            pick_ray.hit_camera = entity;
+            pick_ray.order = camera.order as f32;
+        }
+    };
+    let mut pick_rays: Vec<(PointerId, PickRay)> = Vec::new();
+    for (pointer_id, pointer_location, order_camera) in pointers
+        .iter()
+        .filter(|(pointer_id, ..)| backend_settings.pointer_filter.allows(**pointer_id))
+    {
+        let Some(pointer_location) = pointer_location.location() else {
+            continue;
         };
-        for (cam_entity, camera, ray, cam_layers) in picking_cameras
+        let mut cameras: Vec<(Entity, &Camera)> = picker
+            .cameras
             .iter()
-            .filter(|(_, camera, ..)| {
-                camera.is_active && pointer_location.is_in_viewport(camera, &primary_window_entity)
+            .filter(|(_, camera, _, _, _, cam_settings, ..)| {
+                RapierPickableCamera::allows(*cam_settings, camera)
+                    && is_in_viewport(camera, pointer_location, primary_window)
             })
-            .filter(|(.., marker, _)| marker.is_some() || !backend_settings.require_markers)
-            .filter_map(|(entity, camera, transform, _, layers)| {
-                let mut viewport_pos = pointer_location.position;
-                if let Some(viewport) = &camera.viewport {
-                    viewport_pos -= camera.logical_viewport_rect().unwrap().min;
+            .filter(|(_, _, _, marker, ..)| marker.is_some() || !backend_settings.require_markers)
+            .map(|(entity, camera, ..)| (entity, camera))
+            .collect();
+        if backend_settings.topmost_camera_only {
+            cameras = topmost_camera(cameras, |(_, camera)| camera);
+        }
+        let first_ray = pick_rays.len();
+        for (entity, camera) in cameras {
+            let viewport_pos = viewport_position(camera, pointer_location);
+            let last_viewport_pos = match viewport_pos {
+                Some(viewport_pos) if backend_settings.swept_pointer => {
+                    last_positions.insert((*pointer_id, entity), viewport_pos)
                 }
-                camera
-                    .viewport_to_world(transform, viewport_pos)
-                    .map(|ray| (entity, camera, ray, layers))
+                _ => None,
+            };
+            let Some(mut pick_ray) = viewport_pos
+                .and_then(|viewport_pos| picker.pick_ray(entity, viewport_pos, last_viewport_pos))
+            else {
+                if backend_settings.log_ray_failures && failed_cameras.insert(entity) {
+                    warn!(
+                        "The rapier picking backend could not build a ray through camera {entity:?}, \
+                        so it can't be picked through. Check the camera's viewport and projection."
+                    );
+                }
+                continue;
+            };
+            order_as(&mut pick_ray, order_camera);
+            if backend_settings.record_rays {
+                details.rays.0.insert((*pointer_id, entity), pick_ray.ray);
+            }
+            pick_rays.push((*pointer_id, pick_ray));
+        }
+        // No ray is cast for a pointer outside of every viewport, so report here that it hits
+        // nothing.
+        if pick_rays.len() == first_ray && (emit_empty_hits || just_disabled) {
+            output_events.send(PointerHits::new(
+                *pointer_id,
+                Vec::new(),
+                backend_settings.order_offset,
+            ));
+        }
+    }
+    // Forget pointers that left a camera, so they aren't swept from where they left once back.
+    last_positions.retain(|key, _| {
+        pick_rays
+            .iter()
+            .any(|(pointer, pick_ray)| (*pointer, pick_ray.camera) == *key)
+    });
+    for (entity, pointer_id, ray_pointer, order_camera) in ray_pointers
+        .iter()
+        .filter(|(_, pointer_id, ..)| backend_settings.pointer_filter.allows(**pointer_id))
+    {
+        let Some(mut pick_ray) = picker.ray_pointer_ray(entity, ray_pointer) else {
+            continue;
+        };
+        order_as(&mut pick_ray, order_camera);
+        if backend_settings.record_rays {
+            details.rays.0.insert((*pointer_id, entity), pick_ray.ray);
+        }
+        pick_rays.push((*pointer_id, pick_ray));
+    }
+
+    let Some(rapier_context) = picker
+        .rapier_context
+        .as_deref()
+        .filter(|_| !nothing_pickable)
+    else {
+        // Report the empty hits without casting.
+        for (pointer, pick_ray) in &pick_rays {
+            let order = pick_ray.order + backend_settings.order_offset;
+            output_events.send(PointerHits::new(*pointer, Vec::new(), order));
+        }
+        return;
+    };
+
+    // Rapier's context changes with every physics step, so the cache only survives while physics
+    // isn't running.
+    let cache_valid = backend_settings.cache_static_pointers
+        && !picker.backend_settings.is_changed()
+        && picker
+            .rapier_context
+            .as_ref()
+            .is_some_and(|rapier_context| !rapier_context.is_changed());
+    if !cache_valid {
+        cached_hits.clear();
+    }
+    let cache = &*cached_hits;
+    let cast = |(pointer, pick_ray): &(PointerId, PickRay)| match cache
+        .get(&(*pointer, pick_ray.camera))
+        .filter(|(ray, _)| *ray == pick_ray.ray)
+    {
+        Some((_, hits)) => hits
+            .iter()
+            .map(|&(entity, depth, hit)| RayHit { entity, depth, hit })
+            .collect(),
+        None => picker.cast(rapier_context, pick_ray),
+    };
+    let mut results: Vec<Vec<RayHit>> = if backend_settings.parallel && pick_rays.len() > 1 {
+        let task_pool = ComputeTaskPool::get_or_init(TaskPool::default);
+        let chunk_size = (pick_rays.len() / task_pool.thread_num().max(1)).max(1);
+        pick_rays
+            .par_chunk_map(task_pool, chunk_size, |chunk| {
+                chunk.iter().map(cast).collect::<Vec<_>>()
             })
-        {
-            // if we hit a backface, start a new trace just in front of the surface we hit
-            // we can do this to skip backfaces when picking
-            let mut ray_start = ray.origin;
-            let mut collected_toi = 0.0;
-            for iteration in 0..2 {
-                if let Some((entity, hit_data)) = rapier_context
-                    .cast_ray_and_get_normal(
-                        ray_start,
-                        ray.direction,
-                        f32::MAX,
-                        true,
-                        QueryFilter::new().predicate(&|entity| {
-                            let marker_requirement =
-                                !backend_settings.require_markers || marked_targets.get(entity).is_ok();
-
-                            // Cameras missing render layers intersect all layers
-                            let cam_layers = cam_layers.copied().unwrap_or(RenderLayers::all());
-                            // Other entities missing render layers are on the default layer 0
-                            let entity_layers = layers.get(entity).copied().unwrap_or_default();
-                            let render_layers_match = cam_layers.intersects(&entity_layers);
-
-                            let pickable = pickables
-                                .get(entity)
-                                .map(|p| *p != Pickable::IGNORE)
-                                .unwrap_or(true);
-                            marker_requirement && render_layers_match && pickable
-                        }),
-                    )
-                    .and_then(|(entity, hit)| {
-                        if let Ok((collider, global_transform)) = q_colliders.get(entity) {
-                            let point = OPoint::<f32, Const<3>>::new(hit.point.x, hit.point.y, hit.point.z);
-                            if let Some(surface_normal) = collider.raw.feature_normal_at_point(hit.feature, &point) {
-                                // transform the surface normal back into world space
-                                let transformed_surface_normal = global_transform.compute_matrix().transform_vector3(surface_normal.into());
-                                let is_backface = ray.direction.normalize_or_zero().dot(transformed_surface_normal) > 0.0;
-                                if is_backface {
-                                    ray_start = hit.point + ray.direction * 0.1;
-                                    collected_toi += hit.toi + 0.1;
-                                    return None;
-                                }
-                            }
-                        }
-
-                        let hit_data =
-                            HitData::new(cam_entity, collected_toi + hit.toi, Some(hit.point), Some(hit.normal));
-                        Some((entity, hit_data))
-                    })
+            .into_iter()
+            .flatten()
+            .collect()
+    } else {
+        pick_rays.iter().map(cast).collect()
+    };
+
+    #[cfg(feature = "diagnostics")]
+    if let Some(pick_counts) = pick_counts.as_mut() {
+        let (predicate_evaluations, ray_casts) = picker.take_counts();
+        pick_counts.predicate_evaluations = predicate_evaluations;
+        pick_counts.ray_casts = ray_casts;
+    }
+
+    if backend_settings.cache_static_pointers {
+        cached_hits.clear();
+        for ((pointer, pick_ray), hits) in pick_rays.iter().zip(&results) {
+            let hits = hits
+                .iter()
+                .map(|hit| (hit.entity, hit.depth, hit.hit))
+                .collect();
+            cached_hits.insert((*pointer, pick_ray.camera), (pick_ray.ray, hits));
+        }
+    }
+
+    if backend_settings.hit_stickiness > 0.0 {
+        // Remember the nearest hit of each pointer and camera, and fall back to it when the ray
+        // misses everything but still points close to it.
+        for ((pointer, pick_ray), hits) in pick_rays.iter().zip(results.iter_mut()) {
+            let key = (*pointer, pick_ray.camera);
+            if let Some(nearest) = hits.first() {
+                last_hits.insert(key, (nearest.entity, nearest.hit));
+            } else if let Some(&(entity, hit)) = last_hits.get(&key) {
+                let to_hit = hit.point - pick_ray.ray.origin;
+                let depth = to_hit.length();
+                if (backend_settings.min_ray_distance..=pick_ray.max_toi).contains(&depth)
+                    && pick_ray.ray.direction.angle_between(to_hit)
+                        <= backend_settings.hit_stickiness
+                    && picker.colliders.contains(entity)
+                    && picker.can_pick(entity, pick_ray)
                 {
-                    output_events.send(PointerHits::new(
-                        *pointer_id,
-                        vec![(entity, hit_data)],
-                        camera.order as f32,
-                    ));
-                    break;
+                    let depth = depth + picker.depth_bias(entity);
+                    hits.push(RayHit { entity, depth, hit });
+                }
+            }
+        }
+        last_hits.retain(|key, _| {
+            pick_rays
+                .iter()
+                .any(|(pointer, pick_ray)| (*pointer, pick_ray.camera) == *key)
+        });
+    } else {
+        last_hits.clear();
+    }
+
+    let provides_hit_details = backend_settings.provides_hit_details();
+    // The hits of each pointer, with the camera order they are sent with.
+    let mut outputs: Vec<(PointerId, f32, Vec<(Entity, HitData)>)> = Vec::new();
+    for ((pointer, pick_ray), hits) in pick_rays.iter().zip(results) {
+        if hits.is_empty()
+            && backend_settings.emit_max_distance_point
+            && pick_ray.max_toi < f32::MAX
+        {
+            let ray = pick_ray.ray;
+            // A reversed ray already starts at the far end of the ray through the camera.
+            let miss_point = match pick_ray.reversed {
+                true => RapierMissPoint {
+                    point: ray.origin,
+                    direction: -ray.direction,
+                },
+                false => RapierMissPoint {
+                    point: ray.origin + ray.direction * pick_ray.max_toi,
+                    direction: ray.direction,
+                },
+            };
+            details
+                .misses
+                .0
+                .insert((*pointer, pick_ray.camera), miss_point);
+        }
+        // Rotates world space directions into the camera's view space.
+        let view_rotation = (backend_settings.provide_view_space_normals
+            && backend_settings.compute_normals)
+            .then(|| picker.cameras.get(pick_ray.camera).ok())
+            .flatten()
+            .map(|(_, _, transform, ..)| transform.to_scale_rotation_translation().1.inverse());
+        let ndc_camera = backend_settings
+            .provide_ndc_depths
+            .then(|| picker.cameras.get(pick_ray.camera).ok())
+            .flatten()
+            .map(|(_, camera, transform, ..)| (camera, transform));
+        let picks: Vec<(Entity, HitData)> = hits
+            .into_iter()
+            .map(|RayHit { entity, depth, hit }| {
+                let anchor = anchors.get(entity).ok();
+                let transform = (anchor.is_some() || backend_settings.provide_local_points)
+                    .then(|| picker.colliders.get(entity).ok())
+                    .flatten()
+                    .map(|(_, _, transform)| transform);
+                let mut position = hit.point;
+                let mut surface_point = None;
+                if let (Some(anchor), Some(transform)) = (anchor, transform) {
+                    surface_point = Some(hit.point);
+                    position = transform.transform_point(anchor.0);
+                }
+                let collider = provides_hit_details
+                    .then(|| rapier_context.entity2collider().get(&entity))
+                    .flatten()
+                    .and_then(|&handle| Some((handle, rapier_context.colliders.get(handle)?)));
+                if let Some((handle, collider)) = collider {
+                    let local_point = transform
+                        .filter(|_| backend_settings.provide_local_points)
+                        .map(|transform| transform.affine().inverse().transform_point3(hit.point));
+                    let trimesh = (backend_settings.provide_trimesh_hits
+                        || backend_settings.provide_smooth_normals)
+                        .then(|| trimesh_hit(rapier_context, entity, &hit))
+                        .flatten();
+                    let smooth_normal = trimesh
+                        .filter(|_| {
+                            backend_settings.provide_smooth_normals
+                                && backend_settings.compute_normals
+                        })
+                        .and_then(|trimesh| {
+                            trimesh_smooth_normal(rapier_context, entity, &trimesh, hit.normal)
+                        });
+                    let span = backend_settings
+                        .provide_exit_points
+                        .then(|| {
+                            let advance = ray_advance(backend_settings, depth);
+                            exit_point(rapier_context, collider, hit.point, pick_ray.ray, advance)
+                        })
+                        .flatten()
+                        .map(|exit| RapierHitSpan {
+                            entry: hit.point,
+                            exit,
+                        });
+                    let ndc_depth = ndc_camera
+                        .and_then(|(camera, transform)| camera.world_to_ndc(transform, hit.point))
+                        .map(|ndc| ndc.z);
+                    let pointer_hit = RapierPointerHit {
+                        pointer: *pointer,
+                        camera: pick_ray.camera,
+                        entity,
+                        handle,
+                        feature: hit.feature,
+                        is_sensor: collider.is_sensor(),
+                        toi: hit.toi,
+                        depth,
+                        local_point,
+                        surface_point,
+                        velocity: backend_settings
+                            .provide_velocities
+                            .then(|| point_velocity(rapier_context, collider.parent(), hit.point)),
+                        compound_child: backend_settings
+                            .provide_compound_children
+                            .then(|| compound_child_index(rapier_context, entity, &hit))
+                            .flatten(),
+                        trimesh: trimesh.filter(|_| backend_settings.provide_trimesh_hits),
+                        smooth_normal,
+                        view_normal: view_rotation.map(|view_rotation| view_rotation * hit.normal),
+                        ndc_depth,
+                        span,
+                    };
+                    details
+                        .hits
+                        .0
+                        .insert((*pointer, entity), pointer_hit.clone());
+                    rapier_hits.send(pointer_hit);
                 }
+                let hit_data = HitData::new(
+                    pick_ray.hit_camera,
+                    depth,
+                    Some(position),
+                    picker.hit_normal(&hit),
+                );
+                (entity, hit_data)
+            })
+            .collect();
+
+        #[cfg(feature = "debug")]
+        if let Some(debug_rays) = debug_rays.as_mut() {
+            debug_rays.0.push(debug::DebugRay {
+                origin: pick_ray.ray.origin,
+                direction: pick_ray.ray.direction,
+                max_distance: pick_ray.max_toi,
+                hits: picks
+                    .iter()
+                    .filter_map(|(_, hit)| hit.position.map(|position| (position, hit.normal)))
+                    .collect(),
+            });
+        }
+
+        let merged = outputs.iter_mut().find(|(merged_pointer, order, _)| {
+            backend_settings.merge_overlapping_cameras
+                && merged_pointer == pointer
+                && *order == pick_ray.order
+        });
+        match merged {
+            Some((_, _, merged_picks)) => merged_picks.extend(picks),
+            None => outputs.push((*pointer, pick_ray.order, picks)),
+        }
+    }
+
+    // The order and depth of the hit each pointer is over.
+    let mut hovered_hits: HashMap<PointerId, (f32, f32)> = HashMap::new();
+    for (pointer, mut order, mut picks) in outputs {
+        if backend_settings.merge_overlapping_cameras {
+            merge_camera_hits(&picker, &mut picks);
+        }
+        if backend_settings.order_by_depth {
+            if let Some((_, nearest)) = picks.first() {
+                order -= depth_order_offset(nearest.depth);
             }
         }
+
+        let nearest = picks
+            .iter()
+            .min_by(|(_, a), (_, b)| a.depth.total_cmp(&b.depth));
+        if let Some((entity, hit)) = nearest {
+            let is_above = match hovered_hits.get(&pointer) {
+                Some(&(hovered_order, hovered_depth)) => {
+                    order > hovered_order || (order == hovered_order && hit.depth < hovered_depth)
+                }
+                None => true,
+            };
+            if is_above {
+                hovered_hits.insert(pointer, (order, hit.depth));
+                details.hovered.0.insert(pointer, Some(*entity));
+            }
+        }
+
+        if !picks.is_empty() || emit_empty_hits {
+            let order = order + backend_settings.order_offset;
+            output_events.send(PointerHits::new(pointer, picks, order));
+        }
+    }
+}
+
+/// Sorts the hits of a pointer through several cameras by depth, keeping the nearest hit on each
+/// entity, then applies the hit mode, selection, and hit limit to the result, as they were only
+/// applied per camera. Used by [`RapierBackendSettings::merge_overlapping_cameras`].
+fn merge_camera_hits(picker: &RapierPicker, picks: &mut Vec<(Entity, HitData)>) {
+    let backend_settings = &*picker.backend_settings;
+    picks.sort_by(|(_, a), (_, b)| a.depth.total_cmp(&b.depth));
+    let mut seen = HashSet::new();
+    picks.retain(|(entity, _)| seen.insert(*entity));
+
+    if backend_settings.selection == HitSelection::Farthest {
+        let farthest = picks.pop();
+        picks.clear();
+        picks.extend(farthest);
+    } else {
+        let kept = match (
+            backend_settings.max_distinct_hits,
+            backend_settings.hit_mode,
+        ) {
+            (Some(_), _) => picks.len(),
+            (None, HitMode::Closest) => 1,
+            (None, HitMode::UntilBlocking) => picks
+                .iter()
+                .position(|(entity, _)| picker.blocks_lower(*entity))
+                .map_or(picks.len(), |blocking| blocking + 1),
+            (None, HitMode::All) => picks.len(),
+        };
+        picks.truncate(kept);
+    }
+    picks.truncate(
+        backend_settings
+            .max_distinct_hits
+            .unwrap_or(backend_settings.max_hits),
+    );
+}
+
+/// Keeps only the camera with the highest [`Camera::order`] out of `cameras`, used by
+/// [`RapierBackendSettings::topmost_camera_only`].
+pub(crate) fn topmost_camera<T>(mut cameras: Vec<T>, camera: impl Fn(&T) -> &Camera) -> Vec<T> {
+    let topmost = (0..cameras.len()).max_by_key(|index| camera(&cameras[*index]).order);
+    match topmost {
+        Some(topmost) => vec![cameras.swap_remove(topmost)],
+        None => cameras,
+    }
+}
+
+/// Maps a depth in `[0, inf)` to an order offset in `[0, 0.5)` that grows with depth, used by
+/// [`RapierBackendSettings::order_by_depth`].
+fn depth_order_offset(depth: f32) -> f32 {
+    let depth = depth.max(0.0);
+    0.5 * depth / (depth + 1.0)
+}
+
+/// Returns `true` if the pointer `location` is within the `camera`'s viewport, on the same render
+/// target.
+///
+/// This works like [`Location::is_in_viewport`], but doesn't panic when there is no primary window,
+/// such as in apps where cameras only render to images. Cameras targeting an [`Image`] are matched
+/// against pointers whose location is on that image, with positions in the image's pixels.
+pub(crate) fn is_in_viewport(
+    camera: &Camera,
+    location: &Location,
+    primary_window: Option<Entity>,
+) -> bool {
+    if camera.target.normalize(primary_window).as_ref() != Some(&location.target) {
+        return false;
+    }
+    camera
+        .logical_viewport_rect()
+        .map(|rect| rect.contains(location.position))
+        .unwrap_or(false)
+}
+
+/// Converts the position of a pointer on a camera's render target into a position in the camera's
+/// viewport. Returns `None` if the camera has a viewport, but its rect can't be computed.
+///
+/// Both positions are in logical pixels, as [`Camera::viewport_to_world`] expects, while the
+/// camera's viewport is set in physical pixels, so it is converted with the target's scale factor.
+pub(crate) fn viewport_position(camera: &Camera, location: &Location) -> Option<Vec2> {
+    match camera.viewport {
+        Some(_) => Some(location.position - camera.logical_viewport_rect()?.min),
+        None => Some(location.position),
+    }
+}
+
+/// Converts the [`HitData::position`] of a hit into a rapier point, for follow-up queries with
+/// rapier's own APIs, without reaching for the nalgebra conversions. Returns `None` if the hit has
+/// no position.
+///
+/// The point is in world units, like the shapes of [`Collider`]s. Rapier's own query pipeline
+/// works in physics units, so divide the point by [`RapierContext::physics_scale`] before using it
+/// there. The methods of [`RapierContext`] itself take a [`Vec3`], and don't need this.
+///
+/// ```
+/// # use bevy_ecs::entity::Entity;
+/// # use bevy_math::Vec3;
+/// # use bevy_picking_core::backend::HitData;
+/// # use bevy_picking_rapier::hit_point_to_rapier;
+/// let hit = HitData::new(Entity::PLACEHOLDER, 1.0, Some(Vec3::X), None);
+/// let point = hit_point_to_rapier(&hit).unwrap();
+/// assert_eq!(point.x, 1.0);
+/// ```
+pub fn hit_point_to_rapier(hit: &HitData) -> Option<Point<Real>> {
+    hit.position.map(Point::from)
+}
+
+/// Finds the triangle `hit` is on, with its barycentric coordinates and world space vertices, if
+/// `entity` has a trimesh collider.
+fn trimesh_hit(
+    rapier_context: &RapierContext,
+    entity: Entity,
+    hit: &RayIntersection,
+) -> Option<TrimeshHit> {
+    let FeatureId::Face(face) = hit.feature else {
+        return None;
+    };
+    let handle = rapier_context.entity2collider().get(&entity)?;
+    let collider = rapier_context.colliders.get(*handle)?;
+    let trimesh = collider.shape().as_trimesh()?;
+    // Faces past the number of triangles are the backfaces of those triangles.
+    let triangle_index = face % trimesh.num_triangles() as u32;
+    let triangle = trimesh.triangle(triangle_index);
+
+    // Rapier's colliders are in physics scale, and the shape has already been scaled.
+    let point = collider
+        .position()
+        .inverse_transform_point(&(hit.point / rapier_context.physics_scale()).into());
+    let [a, b, c] = [triangle.a, triangle.b, triangle.c].map(Vec3::from);
+    let barycentric = barycentric_coordinates(Vec3::from(point), a, b, c)?;
+    let vertices = [triangle.a, triangle.b, triangle.c]
+        .map(|vertex| Vec3::from(collider.position() * vertex) * rapier_context.physics_scale());
+
+    Some(TrimeshHit {
+        triangle_index,
+        barycentric,
+        vertices,
+    })
+}
+
+/// Finds where `ray` leaves `collider` after entering it at the world space `entry`, by casting
+/// against the collider alone from `advance` past the entry. Returns `None` if the ray never leaves
+/// it, as with colliders that aren't closed.
+fn exit_point(
+    rapier_context: &RapierContext,
+    collider: &RawCollider,
+    entry: Vec3,
+    ray: Ray,
+    advance: f32,
+) -> Option<Vec3> {
+    // Rapier's colliders are in physics scale.
+    let scale = rapier_context.physics_scale();
+    let origin = (entry + ray.direction * advance) / scale;
+    let rapier_ray = RapierRay::new(origin.into(), ray.direction.into());
+    // A hollow cast reports the surface around the origin, which is inside the collider.
+    let toi = collider
+        .shape()
+        .cast_ray(collider.position(), &rapier_ray, Real::MAX, false)?;
+    Some((origin + ray.direction * toi) * scale)
+}
+
+/// The world space velocity of the rigid `body` at the world space `point`, or zero without one.
+fn point_velocity(
+    rapier_context: &RapierContext,
+    body: Option<RigidBodyHandle>,
+    point: Vec3,
+) -> Vec3 {
+    let Some(body) = body.and_then(|body| rapier_context.bodies.get(body)) else {
+        return Vec3::ZERO;
+    };
+    // Rapier's bodies are in physics scale.
+    let scale = rapier_context.physics_scale();
+    Vec3::from(body.velocity_at_point(&(point / scale).into())) * scale
+}
+
+/// Interpolates the world space vertex normals of the triangle in `trimesh_hit`, flipped to the
+/// side of `flat_normal`, if `entity` has a trimesh collider with vertex normals.
+fn trimesh_smooth_normal(
+    rapier_context: &RapierContext,
+    entity: Entity,
+    trimesh_hit: &TrimeshHit,
+    flat_normal: Vec3,
+) -> Option<Vec3> {
+    let handle = rapier_context.entity2collider().get(&entity)?;
+    let collider = rapier_context.colliders.get(*handle)?;
+    let trimesh = collider.shape().as_trimesh()?;
+    let vertex_normals = &trimesh.pseudo_normals()?.vertices_pseudo_normal;
+    let indices = trimesh.indices().get(trimesh_hit.triangle_index as usize)?;
+
+    let local_normal = indices
+        .iter()
+        .zip(trimesh_hit.barycentric.to_array())
+        .map(|(&index, weight)| Some(Vec3::from(*vertex_normals.get(index as usize)?) * weight))
+        .sum::<Option<Vec3>>()?;
+    let normal =
+        Vec3::from(collider.position().rotation * Vector::from(local_normal)).try_normalize()?;
+    Some(match normal.dot(flat_normal) < 0.0 {
+        true => -normal,
+        false => normal,
+    })
+}
+
+/// Finds the index of the child shape `hit` is on, if `entity` has a compound collider.
+fn compound_child_index(
+    rapier_context: &RapierContext,
+    entity: Entity,
+    hit: &RayIntersection,
+) -> Option<usize> {
+    let handle = rapier_context.entity2collider().get(&entity)?;
+    let collider = rapier_context.colliders.get(*handle)?;
+    let compound = collider.shape().as_compound()?;
+
+    // Rapier's colliders are in physics scale, and the shape has already been scaled.
+    let point = collider
+        .position()
+        .inverse_transform_point(&(hit.point / rapier_context.physics_scale()).into());
+    // The hit point is on the surface of the child that was hit, so it is the closest one.
+    compound
+        .shapes()
+        .iter()
+        .map(|(position, shape)| shape.distance_to_point(position, &point, true))
+        .enumerate()
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(index, _)| index)
+}
+
+/// Computes the barycentric coordinates of `point` in the triangle `a`, `b`, `c`. Returns `None`
+/// if the triangle is degenerate.
+fn barycentric_coordinates(point: Vec3, a: Vec3, b: Vec3, c: Vec3) -> Option<Vec3> {
+    let (ab, ac, ap) = (b - a, c - a, point - a);
+    let (d00, d01, d11) = (ab.dot(ab), ab.dot(ac), ac.dot(ac));
+    let (d20, d21) = (ap.dot(ab), ap.dot(ac));
+    let denominator = d00 * d11 - d01 * d01;
+    if denominator == 0.0 {
+        return None;
     }
+    let v = (d11 * d20 - d01 * d21) / denominator;
+    let w = (d00 * d21 - d01 * d20) / denominator;
+    Some(Vec3::new(1.0 - v - w, v, w))
 }
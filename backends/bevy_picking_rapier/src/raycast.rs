@@ -0,0 +1,1057 @@
+//! The ray walk shared by the systems of the rapier backend.
+
+#[cfg(feature = "diagnostics")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use bevy_ecs::{prelude::*, system::SystemParam};
+use bevy_math::{Affine3A, Ray, Vec2, Vec3};
+use bevy_render::prelude::*;
+use bevy_render::{
+    primitives::{Aabb, Frustum},
+    view::RenderLayers,
+};
+use bevy_transform::prelude::*;
+
+use bevy_picking_core::backend::prelude::*;
+use bevy_rapier3d::{
+    prelude::*,
+    rapier::prelude::{FeatureId, ShapeType},
+};
+
+use crate::{
+    BackfaceMode, HitMode, HitSelection, IgnoreMode, RapierBackendSettings, RapierDepthBias,
+    RapierPickExclude, RapierPickFilter, RapierPickGroups, RapierPickPriority,
+    RapierPickRayOverride, RapierPickRuleResults, RapierPickTransparent, RapierPickable,
+    RapierPickableCamera, RapierRayPointer, RapierUnpickable, SensorMode,
+};
+
+/// How far before a hit, in world units, the ray is checked for being inside a shape, to tell
+/// whether the hit is on a backface.
+const BACKFACE_PROBE_DISTANCE: f32 = 1e-3;
+/// The distance, in logical pixels, between the rays of a [`RapierBackendSettings::swept_pointer`].
+const SWEEP_SPACING: f32 = 2.0;
+/// The most rays cast for a [`RapierBackendSettings::swept_pointer`] per pointer and camera.
+const MAX_SWEEP_RAYS: usize = 32;
+/// How far past a reported hit, relative to its distance along the ray, the walk to the next hit
+/// continues from. The entities already hit are left out of the next cast, so this only needs to
+/// keep rounding from moving the ray back, and colliders right behind the hit are still found.
+const WALK_RELATIVE_ADVANCE: f32 = 1e-6;
+
+/// A ray to cast through a camera.
+#[derive(Clone)]
+pub(crate) struct PickRay {
+    pub camera: Entity,
+    /// The camera reported as the [`HitData::camera`] of the hits, which is [`Self::camera`] unless
+    /// the pointer has a [`RapierOrderCamera`](crate::RapierOrderCamera).
+    pub hit_camera: Entity,
+    pub order: f32,
+    /// The ray to cast. Its direction is always normalized when the ray is built, so times of
+    /// impact along it are distances in world units, and casts and backface tests can use it as is.
+    pub ray: Ray,
+    pub cam_layers: RenderLayers,
+    pub max_toi: f32,
+    /// Whether the ray is cast backwards, from its maximum distance towards the camera, for
+    /// [`RapierBackendSettings::reverse_cast`].
+    pub reversed: bool,
+    /// Collider entities from the camera's [`RapierPickExclude`] that the ray passes through.
+    pub excluded: Vec<Entity>,
+    /// The extra rays cast around the pointer for [`RapierBackendSettings::samples`].
+    pub samples: Vec<Ray>,
+    /// The rays between the pointer's current and last positions for
+    /// [`RapierBackendSettings::swept_pointer`], nearest to the current position first.
+    pub sweep: Vec<Ray>,
+    /// The groups from the camera's [`RapierPickGroups`], if any.
+    pub groups: Option<Vec<Group>>,
+    /// The camera's frustum, when [`RapierBackendSettings::frustum_cull`] is set.
+    pub frustum: Option<Frustum>,
+}
+
+/// A hit found by [`RapierPicker::cast`].
+pub(crate) struct RayHit {
+    pub entity: Entity,
+    /// The distance along the ray from its origin to the hit, plus the entity's
+    /// [`RapierDepthBias`]. This is not affected by the traces that skipped backfaces or previous
+    /// hits before reaching this one.
+    pub depth: f32,
+    pub hit: RayIntersection,
+}
+
+/// Counts the work done by a [`RapierPicker`] since the counts were last taken, for the
+/// diagnostics. These are atomic so rays can be cast in parallel.
+#[cfg(feature = "diagnostics")]
+#[derive(Default)]
+pub struct CastCounts {
+    predicate_evaluations: AtomicUsize,
+    ray_casts: AtomicUsize,
+}
+
+/// A [`SystemParam`] with everything the [`RapierBackend`](crate::RapierBackend) needs to cast pick
+/// rays into the rapier scene.
+///
+/// Besides being used by the backend itself, this can be added to your own systems to ask what a
+/// pick would hit right now with [`RapierPicker::pick_at`], using the same settings and filtering
+/// as the hits reported by the backend.
+#[derive(SystemParam)]
+pub struct RapierPicker<'w, 's> {
+    pub(crate) cameras: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static Camera,
+            &'static GlobalTransform,
+            Option<&'static RapierPickable>,
+            Option<&'static RenderLayers>,
+            Option<&'static RapierPickableCamera>,
+            Option<&'static RapierPickRayOverride>,
+            Option<&'static RapierPickExclude>,
+            Option<&'static RapierPickGroups>,
+            Option<&'static Frustum>,
+            Option<&'static Projection>,
+        ),
+    >,
+    pub(crate) backend_settings: Res<'w, RapierBackendSettings>,
+    pub(crate) rapier_context: Option<Res<'w, RapierContext>>,
+    pub(crate) filters: RapierPickFilters<'w, Collider>,
+    pub(crate) pickables: Query<'w, 's, &'static Pickable>,
+    pub(crate) marked_targets: Query<'w, 's, &'static RapierPickable>,
+    pub(crate) unpickables: Query<'w, 's, (), With<RapierUnpickable>>,
+    pub(crate) disabled: Query<'w, 's, (), With<ColliderDisabled>>,
+    pub(crate) layers: Query<'w, 's, &'static RenderLayers>,
+    pub(crate) priorities: Query<'w, 's, &'static RapierPickPriority>,
+    pub(crate) depth_biases: Query<'w, 's, &'static RapierDepthBias>,
+    pub(crate) transparent: Query<'w, 's, (), With<RapierPickTransparent>>,
+    pub(crate) collision_groups: Query<'w, 's, &'static CollisionGroups>,
+    #[cfg(feature = "diagnostics")]
+    pub(crate) counts: Local<'s, CastCounts>,
+    pub(crate) controllers: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static KinematicCharacterController,
+            &'static GlobalTransform,
+        ),
+    >,
+    pub(crate) colliders: Query<'w, 's, (Entity, &'static Collider, &'static GlobalTransform)>,
+}
+
+impl RapierPicker<'_, '_> {
+    /// Finds the nearest entity a pick through `camera` at `viewport_pos`, a position in the
+    /// camera's viewport in logical pixels, would hit right now.
+    ///
+    /// This uses the same [`RapierBackendSettings`], filtering, and backface handling as the
+    /// backend, and returns the same [`HitData`] the backend would report. Returns `None` if nothing
+    /// is hit, or `camera` is not a camera.
+    pub fn pick_at(&self, camera: Entity, viewport_pos: Vec2) -> Option<(Entity, HitData)> {
+        let rapier_context = self.rapier_context.as_deref()?;
+        let pick_ray = self.pick_ray(camera, viewport_pos, None)?;
+        let RayHit { entity, depth, hit } =
+            self.cast(rapier_context, &pick_ray).into_iter().next()?;
+        Some((
+            entity,
+            HitData::new(camera, depth, Some(hit.point), self.hit_normal(&hit)),
+        ))
+    }
+
+    /// Builds the ray cast through `camera` at `viewport_pos`, applying the camera's
+    /// [`RapierPickableCamera`] and [`RapierPickRayOverride`] settings. With
+    /// [`RapierBackendSettings::swept_pointer`], rays are also built between `viewport_pos` and
+    /// `last_viewport_pos`, where the pointer was on the previous frame.
+    pub(crate) fn pick_ray(
+        &self,
+        camera: Entity,
+        viewport_pos: Vec2,
+        last_viewport_pos: Option<Vec2>,
+    ) -> Option<PickRay> {
+        let (
+            _,
+            cam,
+            transform,
+            _,
+            layers,
+            cam_settings,
+            ray_override,
+            exclude,
+            groups,
+            frustum,
+            projection,
+        ) = self.cameras.get(camera).ok()?;
+        let forward_ray_at = |viewport_pos| {
+            let mut ray = cam.viewport_to_world(transform, viewport_pos)?;
+            if let Some(ray_override) = ray_override {
+                ray = ray_override.apply(ray, transform);
+            }
+            // Zero sized viewports, such as those of minimized windows or collapsed split screens,
+            // can produce degenerate rays that would silently give garbage hits.
+            let degenerate = !ray.origin.is_finite()
+                || !ray.direction.is_finite()
+                || ray.direction == Vec3::ZERO;
+            (!degenerate).then_some(ray)
+        };
+        let forward_ray = forward_ray_at(viewport_pos)?;
+        let max_toi = cam_settings
+            .and_then(|cam_settings| cam_settings.max_distance)
+            .or_else(|| {
+                projection
+                    .filter(|_| self.backend_settings.ray_distance_from_far_plane)
+                    .map(|projection| far_plane_distance(projection, transform, forward_ray))
+            })
+            .or(self.backend_settings.max_ray_distance)
+            .unwrap_or(f32::MAX);
+        let reverse = self.backend_settings.reverse_cast && max_toi < f32::MAX;
+        let ray_at = |viewport_pos| {
+            let ray = forward_ray_at(viewport_pos)?;
+            Some(match reverse {
+                true => Ray {
+                    origin: ray.origin + ray.direction * max_toi,
+                    direction: -ray.direction,
+                },
+                false => ray,
+            })
+        };
+        let ray = ray_at(viewport_pos)?;
+        let sample_count = self.backend_settings.samples.saturating_sub(1);
+        let samples = (0..sample_count)
+            .filter_map(|i| {
+                let angle = std::f32::consts::TAU * i as f32 / sample_count as f32;
+                let offset = Vec2::from_angle(angle) * self.backend_settings.sample_radius;
+                ray_at(viewport_pos + offset)
+            })
+            .collect();
+        let sweep = match last_viewport_pos {
+            Some(last_viewport_pos) if self.backend_settings.swept_pointer => {
+                let motion = last_viewport_pos - viewport_pos;
+                let steps = ((motion.length() / SWEEP_SPACING).ceil() as usize).min(MAX_SWEEP_RAYS);
+                // Nearest to the current position first, leaving out both ends, which were already
+                // cast on this frame and the last one.
+                (1..steps)
+                    .filter_map(|i| ray_at(viewport_pos + motion * (i as f32 / steps as f32)))
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+        Some(PickRay {
+            camera,
+            hit_camera: camera,
+            order: cam.order as f32,
+            ray,
+            cam_layers: camera_layers(layers, &self.backend_settings),
+            max_toi,
+            reversed: reverse,
+            excluded: exclude
+                .map(|exclude| self.excluded_colliders(exclude))
+                .unwrap_or_default(),
+            samples,
+            sweep,
+            groups: groups.map(|groups| groups.0.clone()),
+            frustum: frustum
+                .filter(|_| self.backend_settings.frustum_cull)
+                .cloned(),
+        })
+    }
+
+    /// Builds the ray cast for the [`RapierRayPointer`] on `entity`, which is reported as the camera
+    /// its hits were made through.
+    pub(crate) fn ray_pointer_ray(
+        &self,
+        entity: Entity,
+        ray_pointer: &RapierRayPointer,
+    ) -> Option<PickRay> {
+        let ray = Ray {
+            origin: ray_pointer.origin,
+            direction: ray_pointer.direction.normalize_or_zero(),
+        };
+        if !ray.origin.is_finite() || ray.direction == Vec3::ZERO {
+            return None;
+        }
+        Some(PickRay {
+            camera: entity,
+            hit_camera: entity,
+            order: 0.0,
+            ray,
+            cam_layers: camera_layers(None, &self.backend_settings),
+            max_toi: self.backend_settings.max_ray_distance.unwrap_or(f32::MAX),
+            reversed: false,
+            excluded: Vec::new(),
+            samples: Vec::new(),
+            sweep: Vec::new(),
+            groups: None,
+            frustum: None,
+        })
+    }
+
+    /// Lists the collider entities excluded by `exclude`, including all colliders attached to any
+    /// rigid bodies it lists.
+    fn excluded_colliders(&self, exclude: &RapierPickExclude) -> Vec<Entity> {
+        let mut excluded = exclude.0.clone();
+        if let Some(rapier_context) = self.rapier_context.as_deref() {
+            for entity in &exclude.0 {
+                let Some(body) = rapier_context
+                    .entity2body()
+                    .get(entity)
+                    .and_then(|handle| rapier_context.bodies.get(*handle))
+                else {
+                    continue;
+                };
+                excluded.extend(
+                    body.colliders()
+                        .iter()
+                        .filter_map(|handle| rapier_context.collider_entity(*handle)),
+                );
+            }
+        }
+        excluded
+    }
+
+    /// Returns the number of predicate evaluations and ray casts since the last call, and resets
+    /// them.
+    #[cfg(feature = "diagnostics")]
+    pub(crate) fn take_counts(&self) -> (usize, usize) {
+        (
+            self.counts.predicate_evaluations.swap(0, Ordering::Relaxed),
+            self.counts.ray_casts.swap(0, Ordering::Relaxed),
+        )
+    }
+
+    /// Returns `true` if no entity could possibly be picked: there are no colliders, or markers are
+    /// required and nothing is marked. This is a cheap check, so it may return `false` even when
+    /// nothing is pickable.
+    pub(crate) fn nothing_pickable(&self) -> bool {
+        (self.colliders.is_empty()
+            && (!self.backend_settings.pick_controller_shapes || self.controllers.is_empty()))
+            || (self.backend_settings.require_markers && self.marked_targets.is_empty())
+    }
+
+    /// Returns `true` if `entity` passes all of the backend's filtering for `pick_ray`.
+    pub(crate) fn can_pick(&self, entity: Entity, pick_ray: &PickRay) -> bool {
+        !pick_ray.excluded.contains(&entity)
+            && (self.backend_settings.pick_disabled_colliders || !self.disabled.contains(entity))
+            && is_pickable(
+                entity,
+                pick_ray.cam_layers,
+                &self.backend_settings,
+                &self.marked_targets,
+                &self.unpickables,
+                &self.layers,
+                &self.pickables,
+            )
+            && self.filters.allows(entity)
+            && self.in_pick_groups(entity, pick_ray)
+            && self.in_frustum(entity, pick_ray)
+    }
+
+    /// Returns `true` if the bounding box of `entity`'s collider intersects the ray's camera
+    /// frustum, or if there is no frustum to check against.
+    fn in_frustum(&self, entity: Entity, pick_ray: &PickRay) -> bool {
+        let Some(frustum) = &pick_ray.frustum else {
+            return true;
+        };
+        let Ok((_, collider, global_transform)) = self.colliders.get(entity) else {
+            return true;
+        };
+        let local_aabb = collider.raw.compute_local_aabb();
+        let aabb = Aabb::from_min_max(Vec3::from(local_aabb.mins), Vec3::from(local_aabb.maxs));
+        // The shape is already scaled, as in `is_backface`.
+        let (_, rotation, translation) = global_transform.to_scale_rotation_translation();
+        let model_to_world = Affine3A::from_rotation_translation(rotation, translation);
+        frustum.intersects_obb(&aabb, &model_to_world, true, true)
+    }
+
+    /// Returns `true` if `entity` is a member of any of the ray's [`RapierPickGroups`], or if the
+    /// camera doesn't restrict the groups it picks.
+    fn in_pick_groups(&self, entity: Entity, pick_ray: &PickRay) -> bool {
+        let Some(groups) = &pick_ray.groups else {
+            return true;
+        };
+        let memberships = self
+            .collision_groups
+            .get(entity)
+            .map_or(Group::ALL, |collision_groups| collision_groups.memberships);
+        groups.iter().any(|group| memberships.intersects(*group))
+    }
+
+    /// The normal reported for `hit`, unless [`RapierBackendSettings::compute_normals`] is off.
+    pub(crate) fn hit_normal(&self, hit: &RayIntersection) -> Option<Vec3> {
+        self.backend_settings.compute_normals.then_some(hit.normal)
+    }
+
+    /// Returns `true` if `entity` stops [`HitMode::UntilBlocking`] rays.
+    pub(crate) fn blocks_lower(&self, entity: Entity) -> bool {
+        self.pickables
+            .get(entity)
+            .map(|pickable| pickable.should_block_lower)
+            .unwrap_or(true)
+    }
+
+    /// Returns the [`RapierDepthBias`] of `entity`, or `0` if it has none.
+    pub(crate) fn depth_bias(&self, entity: Entity) -> f32 {
+        self.depth_biases.get(entity).map_or(0.0, |bias| bias.0)
+    }
+
+    /// Returns `true` if `entity` is hit but hides itself and everything behind it, as set by
+    /// [`IgnoreMode::Occluding`].
+    fn occludes(&self, entity: Entity) -> bool {
+        self.backend_settings.ignore_mode == IgnoreMode::Occluding
+            && is_ignored(entity, &self.pickables)
+    }
+
+    /// Returns how large `entity`'s collider looks from the origin of `pick_ray`, as the radius of
+    /// its bounding sphere over the distance to the sphere's center. Entities without a collider
+    /// have no size.
+    fn apparent_size(&self, entity: Entity, pick_ray: &PickRay) -> f32 {
+        let Ok((_, collider, global_transform)) = self.colliders.get(entity) else {
+            return 0.0;
+        };
+        let sphere = collider.raw.compute_local_bounding_sphere();
+        // The shape is already scaled, as in `is_backface`.
+        let (_, rotation, translation) = global_transform.to_scale_rotation_translation();
+        let center = translation + rotation * Vec3::from(*sphere.center());
+        sphere.radius() / center.distance(pick_ray.ray.origin).max(f32::EPSILON)
+    }
+
+    /// Returns `true` if `hit` is on a backface of `entity`'s collider that should be skipped,
+    /// according to [`RapierBackendSettings::backface_culling`]. The `ray_direction` must be
+    /// normalized, as it is in every [`PickRay`].
+    fn is_backface(&self, entity: Entity, hit: &RayIntersection, ray_direction: Vec3) -> bool {
+        if !self.backend_settings.compute_normals {
+            return false;
+        }
+        // Rapier can hit colliders whose components can't be queried, such as ones added this
+        // frame. Those hits are kept, and reported with the cast's own normal.
+        let Ok((_, collider, global_transform)) = self.colliders.get(entity) else {
+            return false;
+        };
+        match self.backend_settings.backface_culling {
+            BackfaceMode::All => (),
+            BackfaceMode::TrimeshOnly => match collider.raw.shape_type() {
+                ShapeType::TriMesh | ShapeType::HeightField => (),
+                _ => return false,
+            },
+            BackfaceMode::Off => return false,
+        }
+        // Collider shapes already include the scale of their transform, so the shape's local space
+        // only differs from world space by a rotation and translation. Transforming the normal with
+        // the full matrix would apply the scale a second time, skewing normals of non-uniformly
+        // scaled colliders and making front faces look like backfaces.
+        let (_, rotation, translation) = global_transform.to_scale_rotation_translation();
+        let local_normal = match collider.raw.shape_type() {
+            // Rapier has no feature normals for heightfields, and the cast's normal faces the ray.
+            // The surface of a heightfield always faces up in its local space, though, so the
+            // outward normal is the cast's normal flipped to face up.
+            ShapeType::HeightField => {
+                let local_normal = rotation.inverse() * hit.normal;
+                if local_normal.y < 0.0 {
+                    -local_normal
+                } else {
+                    local_normal
+                }
+            }
+            // Parry has no feature normals for trimeshes either, but the feature of a hit is the
+            // face of the triangle that was hit, so its outward normal is the triangle's normal.
+            ShapeType::TriMesh => {
+                let triangle_normal = match (hit.feature, collider.raw.as_trimesh()) {
+                    (FeatureId::Face(face), Some(trimesh)) => {
+                        // Faces past the number of triangles are the backfaces of those triangles.
+                        let triangle_index = face % trimesh.num_triangles() as u32;
+                        trimesh.triangle(triangle_index).normal()
+                    }
+                    _ => None,
+                };
+                match triangle_normal {
+                    Some(surface_normal) => Vec3::from(surface_normal.into_inner()),
+                    // Without a triangle, such as for shape casts, fall back to the cast's normal,
+                    // which faces the ray, so the hit is never skipped.
+                    None => rotation.inverse() * hit.normal,
+                }
+            }
+            // The features of ray hits on other shapes don't always match their feature normals,
+            // which are flipped for cuboids, and the cast's normal faces the ray even from inside.
+            // These shapes are closed, though, so the hit is on a backface when the ray was inside
+            // the shape just before reaching it. A solid hit from inside, at the ray's origin, is
+            // never skipped.
+            _ => {
+                let probe = (hit.toi * 0.5).min(BACKFACE_PROBE_DISTANCE);
+                return hit.toi > 0.0
+                    && collider.contains_point(
+                        translation,
+                        rotation,
+                        hit.point - ray_direction * probe,
+                    );
+            }
+        };
+        ray_direction.dot(rotation * local_normal) > 0.0
+    }
+
+    /// Casts the ray and any [`RapierBackendSettings::samples`] around it, returning the hits of the
+    /// one with the nearest hit, nearest first. If they all miss, the [`PickRay::sweep`] rays are
+    /// tried in order, then falls back to [`Self::cast_near_ray`] when
+    /// [`RapierBackendSettings::nearest_to_ray_tolerance`] is set.
+    pub(crate) fn cast(&self, rapier_context: &RapierContext, pick_ray: &PickRay) -> Vec<RayHit> {
+        let hits = self.cast_ray(rapier_context, pick_ray);
+        let hits = pick_ray.samples.iter().fold(hits, |nearest, &ray| {
+            let sample_ray = PickRay {
+                ray,
+                ..pick_ray.clone()
+            };
+            let hits = self.cast_ray(rapier_context, &sample_ray);
+            let depth = |hits: &[RayHit]| hits.first().map_or(f32::INFINITY, |hit| hit.depth);
+            if depth(&hits) < depth(&nearest) {
+                hits
+            } else {
+                nearest
+            }
+        });
+        // The sweep only matters when the pointer itself misses.
+        let hits = match hits.is_empty() {
+            true => pick_ray
+                .sweep
+                .iter()
+                .map(|&ray| {
+                    let sweep_ray = PickRay {
+                        ray,
+                        ..pick_ray.clone()
+                    };
+                    self.cast_ray(rapier_context, &sweep_ray)
+                })
+                .find(|hits| !hits.is_empty())
+                .unwrap_or_default(),
+            false => hits,
+        };
+        match self.backend_settings.nearest_to_ray_tolerance {
+            Some(tolerance) if hits.is_empty() && self.backend_settings.pick_shape.is_none() => {
+                self.cast_near_ray(rapier_context, pick_ray, tolerance)
+                    .into_iter()
+                    .collect()
+            }
+            _ => hits,
+        }
+    }
+
+    /// Sweeps a ball of radius `tolerance` along the ray, returning the first collider that comes
+    /// within `tolerance` of it. Used by [`RapierBackendSettings::nearest_to_ray_tolerance`] when
+    /// the ray itself misses.
+    fn cast_near_ray(
+        &self,
+        rapier_context: &RapierContext,
+        pick_ray: &PickRay,
+        tolerance: f32,
+    ) -> Option<RayHit> {
+        let ray = pick_ray.ray;
+        let start_toi = self.backend_settings.min_ray_distance.max(0.0);
+        let remaining_toi = pick_ray.max_toi - start_toi;
+        if remaining_toi <= 0.0 {
+            return None;
+        }
+        // Transparent and occluding entities would have been hit by the ray itself, so they are
+        // never reported by the approximate pick either.
+        let predicate = |entity| {
+            self.can_pick(entity, pick_ray)
+                && !self.transparent.contains(entity)
+                && !self.occludes(entity)
+        };
+        let filter = query_filter(&self.backend_settings).predicate(&predicate);
+        let ray_start = ray.origin + ray.direction * start_toi;
+        #[cfg(feature = "diagnostics")]
+        self.counts.ray_casts.fetch_add(1, Ordering::Relaxed);
+        let (entity, toi) = rapier_context.cast_shape(
+            ray_start,
+            Rot::IDENTITY,
+            ray.direction,
+            &Collider::ball(tolerance),
+            remaining_toi,
+            true,
+            filter,
+        )?;
+        let hit = shape_cast_intersection(ray_start, ray.direction, toi);
+        Some(RayHit {
+            entity,
+            depth: start_toi + hit.toi + self.depth_bias(entity),
+            hit,
+        })
+    }
+
+    /// Walks along the ray, returning the hits that should be reported, nearest first.
+    ///
+    /// If we hit a backface, a new trace is started just in front of the surface we hit; we can do
+    /// this to skip backfaces when picking. When reporting more than the closest hit, the next
+    /// trace starts at each hit, leaving out the entities hit so far, to find the next one.
+    fn cast_ray(&self, rapier_context: &RapierContext, pick_ray: &PickRay) -> Vec<RayHit> {
+        let backend_settings = &*self.backend_settings;
+        let base_filter = query_filter(backend_settings);
+        // Anything closer than the minimum distance is skipped by starting the ray there.
+        let mut start_toi = backend_settings.min_ray_distance.max(0.0);
+        let mut hits: Vec<RayHit> = Vec::new();
+        let mut reported = 0;
+        let max_hits = backend_settings
+            .max_distinct_hits
+            .unwrap_or(backend_settings.max_hits);
+        while let Some((hit, next_toi)) =
+            self.cast_front_face(rapier_context, pick_ray, base_filter, start_toi, &hits)
+        {
+            let entity = hit.entity;
+            // An occluding ignored entity hides everything behind it, without being reported.
+            if self.occludes(entity) {
+                break;
+            }
+            hits.push(hit);
+            start_toi = next_toi;
+            // Transparent colliders are walked past without being reported, so they neither stop
+            // the walk nor count towards the hit limit.
+            if self.transparent.contains(entity) {
+                continue;
+            }
+            reported += 1;
+
+            let keep_going = match (backend_settings.selection, backend_settings.hit_mode) {
+                (HitSelection::Farthest, _) => true,
+                _ if backend_settings.max_distinct_hits.is_some() => true,
+                (_, HitMode::Closest) => false,
+                (_, HitMode::UntilBlocking) => !self.blocks_lower(entity),
+                (_, HitMode::All) => true,
+            };
+            if !keep_going || reported >= max_hits {
+                break;
+            }
+        }
+        let (passed, mut hits): (Vec<RayHit>, Vec<RayHit>) = hits
+            .into_iter()
+            .partition(|hit| self.transparent.contains(hit.entity));
+        // Depth biases can change the order the hits were found in.
+        hits.sort_by(|a, b| a.depth.total_cmp(&b.depth));
+        if backend_settings.selection == HitSelection::Farthest {
+            hits = hits.pop().into_iter().collect();
+        }
+        // Deterministic ties still need the hits at exactly the same depth.
+        let tie_epsilon = backend_settings
+            .priority_epsilon
+            .or(backend_settings.deterministic_ties.then_some(0.0));
+        if let Some(epsilon) = tie_epsilon {
+            if backend_settings.selection != HitSelection::Farthest
+                && backend_settings.hit_mode == HitMode::Closest
+                && backend_settings.max_distinct_hits.is_none()
+                && !hits.is_empty()
+            {
+                let start_toi = backend_settings.min_ray_distance.max(0.0);
+                // The transparent colliders passed on the way are skipped when looking for ties.
+                hits.extend(passed);
+                hits = self.prioritize_ties(
+                    rapier_context,
+                    pick_ray,
+                    base_filter,
+                    start_toi,
+                    epsilon,
+                    hits,
+                );
+            }
+        }
+        hits
+    }
+
+    /// Finds every hit within `epsilon` of the nearest hit, the first in `tied`, returning the one
+    /// with the highest [`RapierPickPriority`]. Among equal priorities, the nearest hit wins. At most
+    /// [`RapierBackendSettings::max_hits`] tied hits are considered. [`RapierPickTransparent`] hits
+    /// in `tied` are skipped, but are never reported.
+    ///
+    /// With [`HitSelection::LargestApparentSize`], equal priorities are ordered by
+    /// [`Self::apparent_size`] before depth. With [`RapierBackendSettings::deterministic_ties`],
+    /// hits that are still tied are ordered by entity.
+    ///
+    /// Tied hits may be too close together for the advancing ray to find, so the ray is cast again
+    /// from `start_toi`, where the nearest hit was found from, skipping the entities found so far.
+    fn prioritize_ties(
+        &self,
+        rapier_context: &RapierContext,
+        pick_ray: &PickRay,
+        base_filter: QueryFilter,
+        start_toi: f32,
+        epsilon: f32,
+        mut tied: Vec<RayHit>,
+    ) -> Vec<RayHit> {
+        let max_depth = tied[0].depth + epsilon;
+        while tied.len() < self.backend_settings.max_hits {
+            let Some((hit, _)) =
+                self.cast_front_face(rapier_context, pick_ray, base_filter, start_toi, &tied)
+            else {
+                break;
+            };
+            if hit.depth > max_depth || self.occludes(hit.entity) {
+                break;
+            }
+            tied.push(hit);
+        }
+        let priority = |hit: &RayHit| {
+            self.priorities
+                .get(hit.entity)
+                .map(|priority| priority.0)
+                .unwrap_or(0)
+        };
+        let apparent_size = |hit: &RayHit| match self.backend_settings.selection {
+            HitSelection::LargestApparentSize => self.apparent_size(hit.entity, pick_ray),
+            _ => 0.0,
+        };
+        tied.into_iter()
+            .filter(|hit| !self.transparent.contains(hit.entity))
+            .max_by(|a, b| {
+                priority(a)
+                    .cmp(&priority(b))
+                    .then(apparent_size(a).total_cmp(&apparent_size(b)))
+                    .then(b.depth.total_cmp(&a.depth))
+                    .then_with(|| {
+                        if self.backend_settings.deterministic_ties {
+                            entity_order(b.entity).cmp(&entity_order(a.entity))
+                        } else {
+                            std::cmp::Ordering::Equal
+                        }
+                    })
+            })
+            .into_iter()
+            .collect()
+    }
+
+    /// Casts along `pick_ray`, starting `start_toi` along it, and returns the first front-facing
+    /// hit, skipping up to [`RapierBackendSettings::max_backface_skips`] backfaces on the way. The
+    /// distance along the ray to continue the walk from, right at the hit, is returned with it.
+    ///
+    /// Entities in `hits` are skipped: when accumulating hits, the ray may start inside an entity
+    /// that was already reported, and it shouldn't be reported twice.
+    fn cast_front_face(
+        &self,
+        rapier_context: &RapierContext,
+        pick_ray: &PickRay,
+        base_filter: QueryFilter,
+        mut start_toi: f32,
+        hits: &[RayHit],
+    ) -> Option<(RayHit, f32)> {
+        let backend_settings = &*self.backend_settings;
+        let ray = pick_ray.ray;
+        let predicate = |entity| {
+            #[cfg(feature = "diagnostics")]
+            self.counts
+                .predicate_evaluations
+                .fetch_add(1, Ordering::Relaxed);
+            !hits.iter().any(|hit| hit.entity == entity) && self.can_pick(entity, pick_ray)
+        };
+        let filter = base_filter.predicate(&predicate);
+
+        for _ in 0..=backend_settings.max_backface_skips {
+            // The ray direction is normalized, so the time of impact is in world units. Each trace
+            // only gets the distance that is left after any previous traces.
+            let remaining_toi = pick_ray.max_toi - start_toi;
+            if remaining_toi <= 0.0 {
+                return None;
+            }
+            // Step along the ray itself rather than restarting from the hit point, which is not on
+            // the ray for shape casts. This keeps the depth equal to the distance from the ray's
+            // origin, no matter how many times the ray was advanced.
+            let ray_start = ray.origin + ray.direction * start_toi;
+            #[cfg(feature = "diagnostics")]
+            self.counts.ray_casts.fetch_add(1, Ordering::Relaxed);
+            let rapier_hit = match &backend_settings.pick_shape {
+                Some(shape) => rapier_context
+                    .cast_shape(
+                        ray_start,
+                        Rot::IDENTITY,
+                        ray.direction,
+                        shape,
+                        remaining_toi,
+                        backend_settings.solid,
+                        filter,
+                    )
+                    .map(|(entity, toi)| {
+                        (
+                            entity,
+                            shape_cast_intersection(ray_start, ray.direction, toi),
+                        )
+                    }),
+                None if backend_settings.use_interpolated_transforms => self.cast_ray_interpolated(
+                    rapier_context,
+                    ray_start,
+                    ray.direction,
+                    remaining_toi,
+                    filter,
+                ),
+                None => rapier_context.cast_ray_and_get_normal(
+                    ray_start,
+                    ray.direction,
+                    remaining_toi,
+                    backend_settings.solid,
+                    filter,
+                ),
+            };
+            let (entity, hit) = if backend_settings.pick_controller_shapes
+                && backend_settings.pick_shape.is_none()
+            {
+                let controller_hit =
+                    self.cast_ray_controllers(ray_start, ray.direction, remaining_toi, &predicate);
+                nearest_hit(rapier_hit, controller_hit)
+            } else {
+                rapier_hit
+            }?;
+
+            let depth = start_toi + hit.toi;
+            if !self.is_backface(entity, &hit, ray.direction) {
+                let next_toi = depth + depth.abs() * WALK_RELATIVE_ADVANCE;
+                let depth = depth + self.depth_bias(entity);
+                return Some((RayHit { entity, depth, hit }, next_toi));
+            }
+            // The entity with the backface can still be hit behind it, so the ray has to be moved
+            // past the backface.
+            start_toi = depth + ray_advance(backend_settings, depth);
+        }
+        None
+    }
+
+    /// Casts a ray against the [`KinematicCharacterController::custom_shape`] of every character
+    /// controller whose entity passes `predicate`, returning the nearest hit. Used by
+    /// [`RapierBackendSettings::pick_controller_shapes`].
+    fn cast_ray_controllers(
+        &self,
+        ray_origin: Vec3,
+        ray_direction: Vec3,
+        max_toi: f32,
+        predicate: &dyn Fn(Entity) -> bool,
+    ) -> Option<(Entity, RayIntersection)> {
+        // These shapes are only known to the controller, not to rapier's query pipeline, so they
+        // are never sensors.
+        if self.backend_settings.sensor_mode == SensorMode::Only {
+            return None;
+        }
+        let mut nearest: Option<(Entity, RayIntersection)> = None;
+        for (entity, controller, transform) in &self.controllers {
+            let Some((shape, translation, rotation)) = &controller.custom_shape else {
+                continue;
+            };
+            if !predicate(entity) {
+                continue;
+            }
+            let (_, entity_rotation, entity_translation) =
+                transform.to_scale_rotation_translation();
+            let max_toi = nearest.map_or(max_toi, |(_, hit)| hit.toi);
+            if let Some(hit) = shape.cast_ray_and_get_normal(
+                entity_translation + entity_rotation * *translation,
+                entity_rotation * *rotation,
+                ray_origin,
+                ray_direction,
+                max_toi,
+                self.backend_settings.solid,
+            ) {
+                nearest = Some((entity, hit));
+            }
+        }
+        nearest
+    }
+
+    /// Casts a ray against every collider at its [`GlobalTransform`], rather than at its position
+    /// in rapier's last physics step, returning the nearest hit that passes `filter`. Used by
+    /// [`RapierBackendSettings::use_interpolated_transforms`].
+    ///
+    /// This tests every collider in turn instead of using rapier's acceleration structure, so it
+    /// gets slower as the number of colliders grows.
+    fn cast_ray_interpolated(
+        &self,
+        rapier_context: &RapierContext,
+        ray_origin: Vec3,
+        ray_direction: Vec3,
+        max_toi: f32,
+        filter: QueryFilter,
+    ) -> Option<(Entity, RayIntersection)> {
+        rapier_context.with_query_filter(filter, |rapier_filter| {
+            let mut nearest: Option<(Entity, RayIntersection)> = None;
+            for (entity, collider, transform) in &self.colliders {
+                let Some(&handle) = rapier_context.entity2collider().get(&entity) else {
+                    continue;
+                };
+                let Some(raw_collider) = rapier_context.colliders.get(handle) else {
+                    continue;
+                };
+                if !rapier_filter.test(&rapier_context.bodies, handle, raw_collider) {
+                    continue;
+                }
+                // The collider component is in world units, and already includes the scale of the
+                // transform.
+                let (_, rotation, translation) = transform.to_scale_rotation_translation();
+                let max_toi = nearest.map_or(max_toi, |(_, hit)| hit.toi);
+                if let Some(hit) = collider.cast_ray_and_get_normal(
+                    translation,
+                    rotation,
+                    ray_origin,
+                    ray_direction,
+                    max_toi,
+                    self.backend_settings.solid,
+                ) {
+                    nearest = Some((entity, hit));
+                }
+            }
+            nearest
+        })
+    }
+}
+
+/// Returns whichever of two optional hits is closer to the start of the ray.
+fn nearest_hit(
+    a: Option<(Entity, RayIntersection)>,
+    b: Option<(Entity, RayIntersection)>,
+) -> Option<(Entity, RayIntersection)> {
+    match (a, b) {
+        (Some(a), Some(b)) if b.1.toi < a.1.toi => Some(b),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Converts the result of a shape cast along a ray into a [`RayIntersection`], so it can be handled
+/// the same way as a raycast.
+fn shape_cast_intersection(ray_origin: Vec3, ray_direction: Vec3, toi: Toi) -> RayIntersection {
+    let (point, normal) = match toi.details {
+        // Witness 1 and normal 1 are on the collider that was hit, in world space.
+        Some(details) => (details.witness1, details.normal1),
+        // The shape started out penetrating the collider, there is no contact point.
+        None => (ray_origin + ray_direction * toi.toi, -ray_direction),
+    };
+    RayIntersection {
+        toi: toi.toi,
+        point,
+        normal,
+        feature: FeatureId::Unknown,
+    }
+}
+
+/// The distance to advance a ray past a hit at `depth` before casting it again.
+pub(crate) fn ray_advance(backend_settings: &RapierBackendSettings, depth: f32) -> f32 {
+    // Well above the relative precision of an f32, so the new origin is always past the hit.
+    const RELATIVE_ADVANCE: f32 = 1e-4;
+    backend_settings
+        .ray_advance_bias
+        .max(depth.abs() * RELATIVE_ADVANCE)
+}
+
+/// The distance along `ray` to the far clipping plane of a camera with `projection` at `transform`,
+/// or [`f32::MAX`] if the far plane is infinitely far away or never reached.
+fn far_plane_distance(projection: &Projection, transform: &GlobalTransform, ray: Ray) -> f32 {
+    let far = match projection {
+        Projection::Perspective(perspective) => perspective.far,
+        Projection::Orthographic(orthographic) => orthographic.far,
+    };
+    let forward = transform.forward();
+    let cos = ray.direction.dot(forward);
+    if !far.is_finite() || cos <= 0.0 {
+        return f32::MAX;
+    }
+    let far_point = transform.translation() + forward * far;
+    ((far_point - ray.origin).dot(forward) / cos).max(0.0)
+}
+
+/// Builds the [`QueryFilter`] used for raycasts from the [`RapierBackendSettings`], without any
+/// predicate.
+pub(crate) fn query_filter(backend_settings: &RapierBackendSettings) -> QueryFilter<'static> {
+    let mut filter = QueryFilter::new();
+    if let Some(groups) = backend_settings.groups {
+        filter = filter.groups(groups);
+    }
+    // These map directly onto rapier's own body type filtering, and are combined with the
+    // predicate added to the returned filter.
+    filter.flags.set(
+        QueryFilterFlags::EXCLUDE_FIXED,
+        backend_settings.exclude_fixed,
+    );
+    filter.flags.set(
+        QueryFilterFlags::EXCLUDE_DYNAMIC,
+        backend_settings.exclude_dynamic,
+    );
+    filter.flags.set(
+        QueryFilterFlags::EXCLUDE_KINEMATIC,
+        backend_settings.exclude_kinematic,
+    );
+    match backend_settings.sensor_mode {
+        SensorMode::Include => filter,
+        SensorMode::Exclude => filter.exclude_sensors(),
+        SensorMode::Only => filter.exclude_solids(),
+    }
+}
+
+/// Returns `true` if `entity` should be considered by a raycast from a camera with `cam_layers`,
+/// taking into account the [`RapierBackendSettings`], [`RapierPickable`] and [`RapierUnpickable`]
+/// markers, [`RenderLayers`], and [`Pickable`]. Shared by the 2d and 3d backends so they filter
+/// entities identically.
+///
+/// This is run by rapier for every candidate collider of every raycast, so the camera's layers are
+/// resolved by the caller with [`camera_layers`], and each check returns as early as possible.
+pub(crate) fn is_pickable(
+    entity: Entity,
+    cam_layers: RenderLayers,
+    backend_settings: &RapierBackendSettings,
+    marked_targets: &Query<&RapierPickable>,
+    unpickables: &Query<(), With<RapierUnpickable>>,
+    layers: &Query<&RenderLayers>,
+    pickables: &Query<&Pickable>,
+) -> bool {
+    if unpickables.contains(entity) {
+        return false;
+    }
+    if backend_settings.require_markers && !marked_targets.contains(entity) {
+        return false;
+    }
+    // Entities missing render layers are on the configured default layers, layer 0 by default
+    let entity_layers = layers
+        .get(entity)
+        .copied()
+        .unwrap_or(backend_settings.default_entity_layers);
+    if !cam_layers.intersects(&entity_layers) {
+        return false;
+    }
+    // Ignored entities that occlude are hit, then dropped from the hits by the caller.
+    backend_settings.ignore_mode == IgnoreMode::Occluding || !is_ignored(entity, pickables)
+}
+
+/// The order of entities in [`RapierBackendSettings::deterministic_ties`], lowest first: by index,
+/// then by generation.
+fn entity_order(entity: Entity) -> (u32, u32) {
+    (entity.index(), entity.generation())
+}
+
+/// Returns `true` if `entity` has [`Pickable::IGNORE`].
+pub(crate) fn is_ignored(entity: Entity, pickables: &Query<&Pickable>) -> bool {
+    pickables
+        .get(entity)
+        .is_ok_and(|pickable| *pickable == Pickable::IGNORE)
+}
+
+/// The render layers used to filter the entities picked through a camera with `cam_layers`,
+/// without the [`RapierBackendSettings::disabled_layers`].
+pub(crate) fn camera_layers(
+    cam_layers: Option<&RenderLayers>,
+    backend_settings: &RapierBackendSettings,
+) -> RenderLayers {
+    // Cameras missing render layers use the configured default layers, all layers by default
+    let cam_layers = cam_layers
+        .copied()
+        .unwrap_or(backend_settings.default_camera_layers);
+    backend_settings
+        .disabled_layers
+        .iter()
+        .fold(cam_layers, |cam_layers, layer| cam_layers.without(layer))
+}
+
+/// The user's [`RapierPickFilter`] and the results of their
+/// [`RapierPickRule`](crate::RapierPickRule)s for colliders of type `C`, as checked by both
+/// backends on top of their own filtering.
+#[derive(SystemParam)]
+pub struct RapierPickFilters<'w, C: Component> {
+    filter: Option<Res<'w, RapierPickFilter>>,
+    rules: Option<Res<'w, RapierPickRuleResults<C>>>,
+}
+
+impl<C: Component> RapierPickFilters<'_, C> {
+    /// Returns `true` if `entity` passes the user's filter and rules, or if there are none.
+    pub fn allows(&self, entity: Entity) -> bool {
+        let rules_allow = match &self.rules {
+            Some(rules) => rules.allows(entity),
+            None => true,
+        };
+        rules_allow
+            && match &self.filter {
+                Some(filter) => filter.allows(entity),
+                None => true,
+            }
+    }
+}
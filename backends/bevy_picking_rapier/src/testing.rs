@@ -0,0 +1,233 @@
+//! Helpers for testing apps that use the [`RapierBackend`], enabled by the `testing` feature.
+//!
+//! Picking against rapier needs a [`RapierContext`] holding the colliders, an up to date query
+//! pipeline, and a pointer, all of which are normally set up by the physics plugin, a window, and
+//! input. These helpers set up the same inputs for the real [`update_hits`](crate::update_hits)
+//! without any of those, so that picks can be checked in a headless test, and so that bugs can be
+//! reproduced in a few lines:
+//!
+//! ```
+//! # use bevy_transform::prelude::*;
+//! # use bevy_picking_rapier::testing::*;
+//! let mut app = test_app();
+//! let cuboid = spawn_pickable_cuboid(&mut app, Transform::default());
+//! assert_eq!(pick_center(&mut app), Some(cuboid));
+//! ```
+//!
+//! The simplest picks, with [`pick_along`] and [`pick_center`], are made with a
+//! [`RapierRayPointer`], so they go through the same filtering as every other rapier pick without
+//! needing a camera. To pick through a camera instead, spawn a window with [`spawn_window`] and a
+//! camera with [`spawn_camera`], then pick with [`pick_at`]. No window is actually opened: the
+//! camera's view of the window is computed the same way Bevy's renderer would.
+
+use bevy_app::prelude::*;
+use bevy_asset::{AssetEvent, Assets};
+use bevy_ecs::{event::ManualEventReader, prelude::*, system::RunSystemOnce};
+use bevy_math::{Ray, Vec2, Vec3};
+use bevy_render::{
+    camera::{camera_system, ManualTextureViews, NormalizedRenderTarget},
+    prelude::*,
+};
+use bevy_transform::prelude::*;
+use bevy_utils::Uuid;
+use bevy_window::{
+    PrimaryWindow, Window, WindowCreated, WindowRef, WindowResized, WindowResolution,
+};
+
+use bevy_picking_core::{backend::prelude::*, pointer::Location, CorePlugin};
+use bevy_rapier3d::{
+    plugin::systems::{
+        apply_collider_user_changes, apply_scale, init_colliders, init_rigid_bodies,
+    },
+    prelude::*,
+};
+
+use crate::{RapierBackend, RapierRayPointer};
+
+/// The pointer used by [`pick_along`] and [`pick_at`], and whose hits are returned by
+/// [`pointer_hits`].
+pub const TEST_POINTER: PointerId =
+    PointerId::Custom(Uuid::from_u128(0x2f6b_91c4_0d7e_4a35_b8e1_6c94_a3f2_07de));
+
+/// Where [`pick_center`] picks from, looking down the negative z axis at the origin, as a camera
+/// at this position would through the center of its view.
+pub const TEST_VIEW_ORIGIN: Vec3 = Vec3::new(0.0, 0.0, 10.0);
+
+/// The size, in logical pixels, of the windows spawned by [`spawn_window`].
+pub const TEST_WINDOW_SIZE: Vec2 = Vec2::new(800.0, 600.0);
+
+/// Builds an app with the picking core and the [`RapierBackend`], and an empty [`RapierContext`],
+/// without the physics plugin. Colliders are added to the context by [`spawn_pickable_cuboid`] and
+/// [`spawn_collider`], or by [`sync_colliders`] for colliders spawned otherwise.
+pub fn test_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((CorePlugin, RapierBackend))
+        .insert_resource(RapierConfiguration::default())
+        .insert_resource(RapierContext::default())
+        .add_event::<MassModifiedEvent>()
+        // What cameras need to compute their view of a window or of an image.
+        .init_resource::<Assets<Image>>()
+        .init_resource::<ManualTextureViews>()
+        .add_event::<AssetEvent<Image>>()
+        .add_event::<WindowCreated>()
+        .add_event::<WindowResized>();
+    app
+}
+
+/// Spawns a pickable cube with half extents of `0.5` at `transform`, and adds it to the
+/// [`RapierContext`], returning its entity. Like any other collider, the cube is scaled by the
+/// scale of the transform.
+pub fn spawn_pickable_cuboid(app: &mut App, transform: Transform) -> Entity {
+    spawn_collider(app, Collider::cuboid(0.5, 0.5, 0.5), transform)
+}
+
+/// Spawns an entity with `bundle` at `transform`, and adds it to the [`RapierContext`], returning
+/// its entity. The bundle should have a [`Collider`], and can have anything else the collider
+/// needs when it is added, such as a [`RigidBody`] or [`Sensor`].
+pub fn spawn_collider(app: &mut App, bundle: impl Bundle, transform: Transform) -> Entity {
+    let entity = app
+        .world
+        .spawn((
+            bundle,
+            // Transforms aren't propagated, so this sets the global transform directly.
+            TransformBundle {
+                local: transform,
+                global: GlobalTransform::from(transform),
+            },
+        ))
+        .id();
+    sync_colliders(app);
+    entity
+}
+
+/// Adds any new rigid bodies and colliders to the [`RapierContext`], applies the scale of their
+/// transforms and any changes made to them, and updates its query pipeline so rays can hit them, as
+/// the physics plugin would. Entities must have their final [`GlobalTransform`], as transforms
+/// aren't propagated.
+pub fn sync_colliders(app: &mut App) {
+    app.world.run_system_once(apply_scale);
+    app.world.run_system_once(init_rigid_bodies);
+    app.world.run_system_once(init_colliders);
+    app.world.run_system_once(apply_collider_user_changes);
+    let mut rapier_context = app.world.resource_mut::<RapierContext>();
+    rapier_context.propagate_modified_body_positions_to_colliders();
+    rapier_context.update_query_pipeline();
+}
+
+/// Spawns a window of [`TEST_WINDOW_SIZE`] logical pixels, with the given scale factor between its
+/// logical and physical pixels, returning its entity. The first window spawned is the primary
+/// window.
+pub fn spawn_window(app: &mut App, scale_factor: f64) -> Entity {
+    let mut resolution = WindowResolution::default().with_scale_factor_override(scale_factor);
+    resolution.set(TEST_WINDOW_SIZE.x, TEST_WINDOW_SIZE.y);
+    let window = app
+        .world
+        .spawn(Window {
+            resolution,
+            ..Default::default()
+        })
+        .id();
+    let mut primary_windows = app.world.query_filtered::<(), With<PrimaryWindow>>();
+    if primary_windows.iter(&app.world).next().is_none() {
+        app.world.entity_mut(window).insert(PrimaryWindow);
+    }
+    sync_cameras(app);
+    window
+}
+
+/// Spawns `camera` with a perspective projection at `transform`, and computes its view of its
+/// render target, returning its entity. The render target, such as a window from
+/// [`spawn_window`], must already exist.
+pub fn spawn_camera(app: &mut App, camera: Camera, transform: Transform) -> Entity {
+    let entity = app
+        .world
+        .spawn((
+            camera,
+            Projection::default(),
+            // Transforms aren't propagated, so this sets the global transform directly.
+            TransformBundle {
+                local: transform,
+                global: GlobalTransform::from(transform),
+            },
+        ))
+        .id();
+    sync_cameras(app);
+    entity
+}
+
+/// Computes the view of every camera of its render target, as Bevy's renderer would, so rays can be
+/// cast through them. Call this after changing a camera, or the size of a render target.
+pub fn sync_cameras(app: &mut App) {
+    app.world.run_system_once(camera_system::<Projection>);
+}
+
+/// The location of a pointer at `position`, in logical pixels, on `window`.
+pub fn window_location(window: Entity, position: Vec2) -> Location {
+    Location {
+        target: NormalizedRenderTarget::Window(
+            WindowRef::Entity(window)
+                .normalize(None)
+                .expect("window references to an entity are always normalized"),
+        ),
+        position,
+    }
+}
+
+/// Picks from [`TEST_VIEW_ORIGIN`] towards the origin, returning the nearest entity hit, if any.
+pub fn pick_center(app: &mut App) -> Option<Entity> {
+    let ray = Ray {
+        origin: TEST_VIEW_ORIGIN,
+        direction: Vec3::NEG_Z,
+    };
+    pick_along(app, ray).first().map(|(entity, _)| *entity)
+}
+
+/// Runs an update of `app` with a [`RapierRayPointer`] casting `ray`, returning the hits the
+/// backend reported for it, nearest first. The pointer is [`TEST_POINTER`], and is despawned
+/// after the update.
+pub fn pick_along(app: &mut App, ray: Ray) -> Vec<(Entity, HitData)> {
+    pick_with(
+        app,
+        RapierRayPointer {
+            origin: ray.origin,
+            direction: ray.direction,
+        },
+    )
+}
+
+/// Runs an update of `app` with a pointer at `location`, returning the hits the backend reported
+/// for it through every camera, nearest first. The pointer is [`TEST_POINTER`], and is despawned
+/// after the update.
+pub fn pick_at(app: &mut App, location: Location) -> Vec<(Entity, HitData)> {
+    pick_with(
+        app,
+        PointerLocation {
+            location: Some(location),
+        },
+    )
+}
+
+/// Runs an update of `app`, returning every [`PointerHits`] the backend sent for [`TEST_POINTER`]
+/// during it. The pointer must already be spawned, which lets it move between updates.
+pub fn pointer_hits(app: &mut App) -> Vec<PointerHits> {
+    let mut reader = ManualEventReader::<PointerHits>::default();
+    reader.clear(app.world.resource::<Events<PointerHits>>());
+    app.update();
+    reader
+        .read(app.world.resource::<Events<PointerHits>>())
+        .filter(|hits| hits.pointer == TEST_POINTER)
+        .cloned()
+        .collect()
+}
+
+/// Spawns [`TEST_POINTER`] with `pointer`, then runs an update and despawns it, returning all of
+/// its hits, nearest first.
+fn pick_with(app: &mut App, pointer: impl Bundle) -> Vec<(Entity, HitData)> {
+    let pointer = app.world.spawn((TEST_POINTER, pointer)).id();
+    let hits = pointer_hits(app);
+    app.world.despawn(pointer);
+
+    let mut picks: Vec<(Entity, HitData)> = hits.into_iter().flat_map(|hits| hits.picks).collect();
+    picks.sort_by(|(_, a), (_, b)| a.depth.total_cmp(&b.depth));
+    picks
+}
@@ -0,0 +1,138 @@
+//! Checks picking around colliders that aren't built yet, or whose components can't be queried.
+
+use bevy_app::App;
+use bevy_ecs::entity::Entity;
+use bevy_math::{Ray, Vec3};
+use bevy_rapier3d::prelude::*;
+use bevy_transform::prelude::*;
+
+use bevy_picking_rapier::{
+    testing::*, RapierBackendSettings, RapierPendingColliders, RapierRayPointer,
+};
+
+/// Builds a test app that sends empty hits, but not while colliders are pending, with a ray
+/// pointer looking down from the test view origin.
+fn waiting_app() -> App {
+    let mut app = test_app();
+    {
+        let mut settings = app.world.resource_mut::<RapierBackendSettings>();
+        settings.emit_empty_hits = true;
+        settings.wait_for_async_colliders = true;
+    }
+    app.world.spawn((
+        TEST_POINTER,
+        RapierRayPointer {
+            origin: TEST_VIEW_ORIGIN,
+            direction: Vec3::NEG_Z,
+        },
+    ));
+    app
+}
+
+/// Spawns an entity whose collider is still to be built from a mesh, at `transform`.
+fn spawn_async_collider(app: &mut App, transform: Transform) -> Entity {
+    app.world
+        .spawn((
+            AsyncCollider::default(),
+            // Transforms aren't propagated, so this sets the global transform directly.
+            TransformBundle {
+                local: transform,
+                global: GlobalTransform::from(transform),
+            },
+        ))
+        .id()
+}
+
+/// Builds the collider of `entity`, as `bevy_rapier` does once its mesh is loaded.
+fn finish_async_collider(app: &mut App, entity: Entity) {
+    app.world
+        .entity_mut(entity)
+        .remove::<AsyncCollider>()
+        .insert(Collider::cuboid(0.5, 0.5, 0.5));
+    sync_colliders(app);
+}
+
+#[test]
+fn empty_hits_are_held_back_while_colliders_are_pending() {
+    let mut app = waiting_app();
+    let pending = spawn_async_collider(&mut app, Transform::default());
+
+    assert!(pointer_hits(&mut app).is_empty());
+    assert!(app
+        .world
+        .resource::<RapierPendingColliders>()
+        .is_pending(pending));
+
+    // Without waiting, the miss is reported right away.
+    app.world
+        .resource_mut::<RapierBackendSettings>()
+        .wait_for_async_colliders = false;
+    let hits = pointer_hits(&mut app);
+    assert_eq!(hits.len(), 1);
+    assert!(hits[0].picks.is_empty());
+    app.world
+        .resource_mut::<RapierBackendSettings>()
+        .wait_for_async_colliders = true;
+
+    // Hits are still reported while waiting, only misses are held back.
+    let cuboid = spawn_pickable_cuboid(&mut app, Transform::from_xyz(0.0, 0.0, -5.0));
+    let hits = pointer_hits(&mut app);
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].picks.len(), 1);
+    assert_eq!(hits[0].picks[0].0, cuboid);
+}
+
+#[test]
+fn empty_hits_are_released_once_colliders_are_built() {
+    let mut app = waiting_app();
+    // Off the pointer's ray, so the pointer still misses once its collider is built.
+    let pending = spawn_async_collider(&mut app, Transform::from_xyz(5.0, 0.0, 0.0));
+    assert!(pointer_hits(&mut app).is_empty());
+
+    finish_async_collider(&mut app, pending);
+    let hits = pointer_hits(&mut app);
+    assert_eq!(hits.len(), 1);
+    assert!(hits[0].picks.is_empty());
+    assert!(!app
+        .world
+        .resource::<RapierPendingColliders>()
+        .is_pending(pending));
+}
+
+#[test]
+fn colliders_built_under_the_pointer_are_hit() {
+    let mut app = waiting_app();
+    let pending = spawn_async_collider(&mut app, Transform::default());
+    assert!(pointer_hits(&mut app).is_empty());
+
+    finish_async_collider(&mut app, pending);
+    let hits = pointer_hits(&mut app);
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].picks.len(), 1);
+    assert_eq!(hits[0].picks[0].0, pending);
+}
+
+#[test]
+fn hits_on_colliders_that_cant_be_queried_report_the_cast_normal() {
+    let mut app = test_app();
+    let cuboid = spawn_pickable_cuboid(&mut app, Transform::default());
+    // Another collider off the ray, so the scene still has colliders the backend can query.
+    spawn_pickable_cuboid(&mut app, Transform::from_xyz(5.0, 0.0, 0.0));
+    // The collider stays in the rapier context, as it does until physics next syncs, but the
+    // backend can no longer query its components to check for backfaces.
+    app.world.entity_mut(cuboid).remove::<Collider>();
+
+    let picks = pick_along(
+        &mut app,
+        Ray {
+            origin: TEST_VIEW_ORIGIN,
+            direction: Vec3::NEG_Z,
+        },
+    );
+    assert_eq!(picks.len(), 1);
+    let (entity, hit) = &picks[0];
+    assert_eq!(*entity, cuboid);
+    assert!((hit.depth - 9.5).abs() < 1e-4, "{}", hit.depth);
+    let normal = hit.normal.expect("the cast's normal is reported");
+    assert!(normal.distance(Vec3::Z) < 1e-4, "{normal}");
+}
@@ -0,0 +1,332 @@
+//! Checks which surfaces are skipped as backfaces, and the depth of the hits behind them.
+
+use bevy_ecs::entity::Entity;
+use bevy_math::{Ray, Vec3};
+use bevy_rapier3d::prelude::*;
+use bevy_render::prelude::*;
+use bevy_transform::prelude::*;
+
+use bevy_picking_rapier::{testing::*, BackfaceMode, RapierBackendSettings};
+
+#[test]
+fn cuboid_front_faces_are_hit() {
+    let mut app = test_app();
+    let cuboid = spawn_pickable_cuboid(&mut app, Transform::default());
+
+    // Every face of the cube, each from 10 units away from its center.
+    for direction in [
+        Vec3::NEG_Z,
+        Vec3::Z,
+        Vec3::NEG_X,
+        Vec3::X,
+        Vec3::NEG_Y,
+        Vec3::Y,
+    ] {
+        let ray = Ray {
+            origin: -direction * 10.0,
+            direction,
+        };
+        let picks = pick_along(&mut app, ray);
+        assert_eq!(picks.len(), 1, "{direction}");
+        let (entity, hit) = &picks[0];
+        assert_eq!(*entity, cuboid);
+        assert!((hit.depth - 9.5).abs() < 1e-4, "{direction}: {}", hit.depth);
+        assert_eq!(hit.normal, Some(-direction));
+    }
+}
+
+/// A hollow box with half extents of `half_size`, whose faces point outwards.
+fn hollow_box(half_size: f32) -> Collider {
+    let vertices = (0..8)
+        .map(|i| {
+            let sign = |bit| if i & bit == 0 { -half_size } else { half_size };
+            Vec3::new(sign(1), sign(2), sign(4))
+        })
+        .collect();
+    let indices = vec![
+        [0, 2, 3],
+        [0, 3, 1],
+        [4, 5, 7],
+        [4, 7, 6],
+        [0, 4, 6],
+        [0, 6, 2],
+        [1, 3, 7],
+        [1, 7, 5],
+        [0, 1, 5],
+        [0, 5, 4],
+        [2, 6, 7],
+        [2, 7, 3],
+    ];
+    Collider::trimesh(vertices, indices)
+}
+
+/// Spawns three nested hollow boxes around the origin, and a cube outside of them, all sized by
+/// `scale`, then picks from the origin towards the cube, through the back of each box.
+fn pick_through_nested_boxes(max_backface_skips: usize, scale: f32) -> (Entity, Option<Entity>) {
+    let mut app = test_app();
+    app.world
+        .resource_mut::<RapierBackendSettings>()
+        .max_backface_skips = max_backface_skips;
+    for half_size in [1.0, 2.0, 3.0] {
+        spawn_collider(
+            &mut app,
+            hollow_box(half_size * scale),
+            Transform::default(),
+        );
+    }
+    let cuboid = spawn_pickable_cuboid(
+        &mut app,
+        Transform::from_xyz(0.0, 0.0, -4.0 * scale).with_scale(Vec3::splat(scale)),
+    );
+
+    let ray = Ray {
+        origin: Vec3::ZERO,
+        direction: Vec3::NEG_Z,
+    };
+    let picked = pick_along(&mut app, ray).first().map(|(entity, _)| *entity);
+    (cuboid, picked)
+}
+
+#[test]
+fn rays_step_past_each_nested_backface() {
+    let (cuboid, picked) = pick_through_nested_boxes(3, 1.0);
+    assert_eq!(picked, Some(cuboid));
+    let (_, picked) = pick_through_nested_boxes(2, 1.0);
+    assert_eq!(picked, None);
+}
+
+#[test]
+fn rays_step_past_nested_backfaces_in_small_scenes() {
+    // The boxes are a hundredth of a unit apart, so a fixed advance of any noticeable size would
+    // step over them.
+    let (cuboid, picked) = pick_through_nested_boxes(3, 0.01);
+    assert_eq!(picked, Some(cuboid));
+}
+
+/// A quad of two triangles with half extents of `1.0` in the xy plane, facing the positive z axis.
+fn quad() -> Collider {
+    let vertices = vec![
+        Vec3::new(-1.0, -1.0, 0.0),
+        Vec3::new(1.0, -1.0, 0.0),
+        Vec3::new(1.0, 1.0, 0.0),
+        Vec3::new(-1.0, 1.0, 0.0),
+    ];
+    Collider::trimesh(vertices, vec![[0, 1, 2], [0, 2, 3]])
+}
+
+#[test]
+fn quads_are_hit_at_their_distance_from_the_camera() {
+    let mut app = test_app();
+    let window = spawn_window(&mut app, 1.0);
+    spawn_camera(
+        &mut app,
+        Camera::default(),
+        Transform::from_translation(TEST_VIEW_ORIGIN).looking_at(Vec3::ZERO, Vec3::Y),
+    );
+    let quad = spawn_collider(&mut app, quad(), Transform::default());
+
+    let picks = pick_at(&mut app, window_location(window, TEST_WINDOW_SIZE / 2.0));
+    assert_eq!(picks.len(), 1);
+    let (entity, hit) = &picks[0];
+    assert_eq!(*entity, quad);
+    // Rays through cameras start at the near plane, 0.1 in front of the camera.
+    assert!((hit.depth - 9.9).abs() < 1e-4, "{}", hit.depth);
+    assert!(hit.position.unwrap().distance(Vec3::ZERO) < 1e-4);
+}
+
+#[test]
+fn depth_is_the_distance_to_the_hit_after_skipping_backfaces() {
+    let mut app = test_app();
+    // The ray starts inside the box, so it steps past the back of the box before reaching the quad.
+    spawn_collider(
+        &mut app,
+        hollow_box(1.0),
+        Transform::from_translation(TEST_VIEW_ORIGIN),
+    );
+    let quad = spawn_collider(&mut app, quad(), Transform::default());
+
+    let ray = Ray {
+        origin: TEST_VIEW_ORIGIN,
+        direction: Vec3::NEG_Z,
+    };
+    let picks = pick_along(&mut app, ray);
+    assert_eq!(picks.len(), 1);
+    let (entity, hit) = &picks[0];
+    assert_eq!(*entity, quad);
+    assert!((hit.depth - 10.0).abs() < 1e-5, "{}", hit.depth);
+}
+
+/// Picks along a grid of rays from in front of a cube, a ball, a hollow box, and a quad, returning
+/// every hit's entity, depth, and normal.
+fn pick_front_faces(backface_culling: BackfaceMode, max_backface_skips: usize) -> Vec<PickedFace> {
+    let mut app = test_app();
+    let mut settings = app.world.resource_mut::<RapierBackendSettings>();
+    settings.backface_culling = backface_culling;
+    settings.max_backface_skips = max_backface_skips;
+    spawn_pickable_cuboid(&mut app, Transform::from_xyz(-3.0, 0.0, 0.0));
+    spawn_collider(
+        &mut app,
+        Collider::ball(1.0),
+        Transform::from_xyz(3.0, 0.0, 0.0),
+    );
+    spawn_collider(
+        &mut app,
+        hollow_box(1.0),
+        Transform::from_xyz(0.0, 3.0, 0.0),
+    );
+    spawn_collider(&mut app, quad(), Transform::from_xyz(0.0, -3.0, 0.0));
+
+    let mut faces = Vec::new();
+    for x in -8..=8 {
+        for y in -8..=8 {
+            let ray = Ray {
+                origin: TEST_VIEW_ORIGIN + Vec3::new(x as f32, y as f32, 0.0) * 0.5,
+                direction: Vec3::NEG_Z,
+            };
+            faces.extend(
+                pick_along(&mut app, ray)
+                    .into_iter()
+                    .map(|(entity, hit)| (entity, hit.depth, hit.normal)),
+            );
+        }
+    }
+    faces
+}
+
+type PickedFace = (Entity, f32, Option<Vec3>);
+
+#[test]
+fn front_faces_are_hit_the_same_however_backfaces_are_handled() {
+    let faces = pick_front_faces(BackfaceMode::All, 1);
+    assert!(faces.len() > 10, "{}", faces.len());
+    for (mode, skips) in [
+        (BackfaceMode::All, 0),
+        (BackfaceMode::All, 4),
+        (BackfaceMode::TrimeshOnly, 1),
+        (BackfaceMode::Off, 1),
+    ] {
+        assert_eq!(pick_front_faces(mode, skips), faces, "{mode:?}, {skips}");
+    }
+}
+
+#[test]
+fn front_faces_of_non_uniformly_scaled_trimeshes_are_hit() {
+    let mut app = test_app();
+    // A slanted quad facing the positive x and z axes, stretched along x to face mostly along z.
+    let vertices = vec![
+        Vec3::new(-1.0, -1.0, 1.0),
+        Vec3::new(1.0, -1.0, -1.0),
+        Vec3::new(1.0, 1.0, -1.0),
+        Vec3::new(-1.0, 1.0, 1.0),
+    ];
+    let slanted = spawn_collider(
+        &mut app,
+        Collider::trimesh(vertices, vec![[0, 1, 2], [0, 2, 3]]),
+        Transform::from_scale(Vec3::new(10.0, 1.0, 1.0)),
+    );
+
+    // A grazing ray onto the front of the stretched quad, that would be onto its back if the
+    // quad's normal were stretched along with it.
+    let direction = Vec3::new(0.9, 0.0, -0.3).normalize();
+    let ray = Ray {
+        origin: -direction * 5.0,
+        direction,
+    };
+    let picks = pick_along(&mut app, ray);
+    assert_eq!(picks.len(), 1);
+    let (entity, hit) = &picks[0];
+    assert_eq!(*entity, slanted);
+    assert!((hit.depth - 5.0).abs() < 1e-4, "{}", hit.depth);
+}
+
+#[test]
+fn unnormalized_ray_directions_give_the_same_hits() {
+    let mut app = test_app();
+    app.world
+        .resource_mut::<RapierBackendSettings>()
+        .max_ray_distance = Some(20.0);
+    // The ray starts inside the box, and steps past its back before reaching the cube.
+    spawn_collider(
+        &mut app,
+        hollow_box(1.0),
+        Transform::from_translation(TEST_VIEW_ORIGIN),
+    );
+    spawn_pickable_cuboid(&mut app, Transform::default());
+
+    let direction = Vec3::new(0.05, 0.0, -1.0).normalize();
+    let pick = |app: &mut _, direction| {
+        let ray = Ray {
+            origin: TEST_VIEW_ORIGIN,
+            direction,
+        };
+        pick_along(app, ray)
+    };
+    let picks = pick(&mut app, direction);
+    assert_eq!(picks.len(), 1);
+    for scale in [0.01, 7.0] {
+        let scaled_picks = pick(&mut app, direction * scale);
+        assert_eq!(scaled_picks.len(), 1, "{scale}");
+        assert_eq!(scaled_picks[0].0, picks[0].0, "{scale}");
+        let (hit, scaled_hit) = (&picks[0].1, &scaled_picks[0].1);
+        assert!(
+            (scaled_hit.depth - hit.depth).abs() < 1e-4,
+            "{scale}: {}",
+            scaled_hit.depth
+        );
+        assert!(scaled_hit.position.unwrap().distance(hit.position.unwrap()) < 1e-4);
+        assert_eq!(scaled_hit.normal, hit.normal, "{scale}");
+    }
+}
+
+#[test]
+fn sloped_heightfields_are_hit_from_above_and_passed_through_from_below() {
+    let mut app = test_app();
+    // A single cell, 4 units across, rising by 1 unit along one side.
+    let heightfield = spawn_collider(
+        &mut app,
+        Collider::heightfield(vec![0.0, 0.0, 1.0, 1.0], 2, 2, Vec3::new(4.0, 1.0, 4.0)),
+        Transform::default(),
+    );
+    let cuboid = spawn_pickable_cuboid(&mut app, Transform::from_xyz(0.0, 5.0, 0.0));
+
+    let ray = Ray {
+        origin: Vec3::new(0.5, 3.0, 0.3),
+        direction: Vec3::NEG_Y,
+    };
+    let picks = pick_along(&mut app, ray);
+    assert_eq!(picks.len(), 1);
+    let (entity, hit) = &picks[0];
+    assert_eq!(*entity, heightfield);
+    let normal = hit.normal.unwrap();
+    assert!(normal.y > 0.5 && normal.y < 0.99, "{normal}");
+
+    let ray = Ray {
+        origin: Vec3::new(0.5, -3.0, 0.3),
+        direction: Vec3::Y,
+    };
+    assert_eq!(
+        pick_along(&mut app, ray).first().map(|(entity, _)| *entity),
+        Some(cuboid)
+    );
+}
+
+#[test]
+fn backfaces_are_hit_when_culling_is_off() {
+    let mut app = test_app();
+    app.world
+        .resource_mut::<RapierBackendSettings>()
+        .backface_culling = BackfaceMode::Off;
+    let hollow_box = spawn_collider(&mut app, hollow_box(1.0), Transform::default());
+    spawn_pickable_cuboid(&mut app, Transform::from_xyz(0.0, 0.0, -3.0));
+
+    // From inside the box, the first surface is the inside of its far wall.
+    let ray = Ray {
+        origin: Vec3::ZERO,
+        direction: Vec3::NEG_Z,
+    };
+    let picks = pick_along(&mut app, ray);
+    assert_eq!(picks.len(), 1);
+    let (entity, hit) = &picks[0];
+    assert_eq!(*entity, hollow_box);
+    assert!((hit.depth - 1.0).abs() < 1e-5, "{}", hit.depth);
+}
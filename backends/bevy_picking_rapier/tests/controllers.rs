@@ -0,0 +1,54 @@
+//! Checks picking the shapes of character controllers.
+
+use bevy_math::{Quat, Ray, Vec3};
+use bevy_rapier3d::prelude::*;
+use bevy_transform::prelude::*;
+
+use bevy_picking_rapier::{testing::*, RapierBackendSettings};
+
+#[test]
+fn controller_shapes_are_picked_when_enabled() {
+    let mut app = test_app();
+    let controller = spawn_collider(
+        &mut app,
+        KinematicCharacterController {
+            custom_shape: Some((Collider::capsule_y(0.5, 0.5), Vec3::ZERO, Quat::IDENTITY)),
+            ..Default::default()
+        },
+        Transform::default(),
+    );
+    assert_eq!(pick_center(&mut app), None);
+
+    app.world
+        .resource_mut::<RapierBackendSettings>()
+        .pick_controller_shapes = true;
+    let ray = Ray {
+        origin: TEST_VIEW_ORIGIN,
+        direction: Vec3::NEG_Z,
+    };
+    let picks = pick_along(&mut app, ray);
+    assert_eq!(picks.len(), 1);
+    let (entity, hit) = &picks[0];
+    assert_eq!(*entity, controller);
+    // Rays are cast against capsules iteratively, so they are less precise than other shapes.
+    assert!((hit.depth - 9.5).abs() < 1e-3, "{}", hit.depth);
+}
+
+#[test]
+fn controller_shapes_are_picked_at_their_offset() {
+    let mut app = test_app();
+    app.world
+        .resource_mut::<RapierBackendSettings>()
+        .pick_controller_shapes = true;
+    spawn_pickable_cuboid(&mut app, Transform::default());
+    // The controller is behind the cube, but its shape is offset to be in front of it.
+    let controller = spawn_collider(
+        &mut app,
+        KinematicCharacterController {
+            custom_shape: Some((Collider::ball(0.5), Vec3::Z * 4.0, Quat::IDENTITY)),
+            ..Default::default()
+        },
+        Transform::from_xyz(0.0, 0.0, -2.0),
+    );
+    assert_eq!(pick_center(&mut app), Some(controller));
+}
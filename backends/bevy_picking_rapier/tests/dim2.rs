@@ -0,0 +1,161 @@
+//! Checks what the 2d rapier backend reports for the colliders under a pointer.
+#![cfg(feature = "dim2")]
+
+use bevy_app::App;
+use bevy_asset::{AssetEvent, Assets};
+use bevy_ecs::{prelude::*, system::RunSystemOnce};
+use bevy_math::Vec2;
+use bevy_rapier2d::{
+    plugin::systems::{apply_collider_user_changes, apply_scale, init_colliders},
+    prelude::*,
+};
+use bevy_render::{camera::ManualTextureViews, prelude::*};
+use bevy_transform::prelude::*;
+use bevy_window::{PrimaryWindow, WindowCreated, WindowResized};
+
+use bevy_picking_core::{backend::prelude::*, CorePlugin};
+use bevy_picking_rapier::{dim2::RapierBackend2d, testing::*, HitMode, RapierBackendSettings};
+
+/// Builds an app with the picking core, the [`RapierBackend2d`], a window, and a 2d camera at the
+/// origin that shows one world unit per logical pixel.
+fn test_app_2d() -> App {
+    let mut app = App::new();
+    app.add_plugins((CorePlugin, RapierBackend2d))
+        .insert_resource(RapierConfiguration::default())
+        .insert_resource(RapierContext::default())
+        .add_event::<MassModifiedEvent>()
+        .init_resource::<Assets<Image>>()
+        .init_resource::<ManualTextureViews>()
+        .add_event::<AssetEvent<Image>>()
+        .add_event::<WindowCreated>()
+        .add_event::<WindowResized>();
+    spawn_window(&mut app, 1.0);
+    app.world.spawn((
+        Camera::default(),
+        Projection::Orthographic(OrthographicProjection::default()),
+        TransformBundle::from_transform(Transform::from_xyz(0.0, 0.0, 10.0)),
+    ));
+    sync_cameras(&mut app);
+    app
+}
+
+/// Spawns a pickable square with half extents of `half_size` at `position`, and adds it to the
+/// [`RapierContext`].
+fn spawn_square(app: &mut App, half_size: f32, position: Vec2) -> Entity {
+    let transform = Transform::from_translation(position.extend(0.0));
+    let entity = app
+        .world
+        .spawn((
+            Collider::cuboid(half_size, half_size),
+            TransformBundle {
+                local: transform,
+                global: GlobalTransform::from(transform),
+            },
+        ))
+        .id();
+    app.world.run_system_once(apply_scale);
+    app.world.run_system_once(init_colliders);
+    app.world.run_system_once(apply_collider_user_changes);
+    app.world
+        .resource_mut::<RapierContext>()
+        .update_query_pipeline();
+    entity
+}
+
+fn primary_window(app: &mut App) -> Entity {
+    app.world
+        .query_filtered::<Entity, With<PrimaryWindow>>()
+        .single(&app.world)
+}
+
+/// Picks at the world `position`, returning every hit reported for it.
+fn pick_world(app: &mut App, position: Vec2) -> Vec<(Entity, HitData)> {
+    let window_center = TEST_WINDOW_SIZE / 2.0;
+    // Logical pixels grow downwards, while world units grow upwards.
+    let pixel = window_center + Vec2::new(position.x, -position.y);
+    let window = primary_window(app);
+    pick_at(app, window_location(window, pixel))
+}
+
+fn assert_hit_at(hit: &HitData, position: Vec2) {
+    let hit_position = hit.position.expect("2d hits have a position");
+    assert!(
+        hit_position.distance(position.extend(0.0)) < 1e-3,
+        "{hit_position}"
+    );
+    assert_eq!(hit.depth, 0.0);
+    // The pointer is a point, not a ray, so it doesn't hit a surface with a normal.
+    assert_eq!(hit.normal, None);
+}
+
+#[test]
+fn closest_reports_the_collider_under_the_pointer_without_a_normal() {
+    let mut app = test_app_2d();
+    let square = spawn_square(&mut app, 50.0, Vec2::ZERO);
+    spawn_square(&mut app, 20.0, Vec2::new(200.0, 0.0));
+
+    let position = Vec2::new(20.0, 30.0);
+    let picks = pick_world(&mut app, position);
+    assert_eq!(picks.len(), 1);
+    assert_eq!(picks[0].0, square);
+    assert_hit_at(&picks[0].1, position);
+
+    // Even when the pointer is right at the center of a collider, there is no normal to report.
+    let picks = pick_world(&mut app, Vec2::ZERO);
+    assert_eq!(picks.len(), 1);
+    assert_eq!(picks[0].0, square);
+    assert_hit_at(&picks[0].1, Vec2::ZERO);
+
+    assert!(pick_world(&mut app, Vec2::new(100.0, 0.0)).is_empty());
+}
+
+#[test]
+fn all_reports_every_collider_under_the_pointer_without_normals() {
+    let mut app = test_app_2d();
+    let large = spawn_square(&mut app, 50.0, Vec2::ZERO);
+    let small = spawn_square(&mut app, 30.0, Vec2::new(30.0, 30.0));
+    spawn_square(&mut app, 20.0, Vec2::new(200.0, 0.0));
+    app.world.resource_mut::<RapierBackendSettings>().hit_mode = HitMode::All;
+
+    let position = Vec2::new(20.0, 20.0);
+    let picks = pick_world(&mut app, position);
+    let mut entities: Vec<_> = picks.iter().map(|(entity, _)| *entity).collect();
+    entities.sort();
+    let mut expected = vec![large, small];
+    expected.sort();
+    assert_eq!(entities, expected);
+    for (_, hit) in &picks {
+        assert_hit_at(hit, position);
+    }
+
+    // Only the first hits are reported when there are more than `max_hits`.
+    app.world.resource_mut::<RapierBackendSettings>().max_hits = 1;
+    let picks = pick_world(&mut app, position);
+    assert_eq!(picks.len(), 1);
+    assert!(expected.contains(&picks[0].0));
+    assert_hit_at(&picks[0].1, position);
+}
+
+#[test]
+fn disabling_sends_empty_hits_once_even_when_emitting_empty_hits() {
+    let mut app = test_app_2d();
+    app.world
+        .resource_mut::<RapierBackendSettings>()
+        .emit_empty_hits = true;
+    spawn_square(&mut app, 50.0, Vec2::ZERO);
+    let window = primary_window(&mut app);
+    app.world.spawn((
+        TEST_POINTER,
+        PointerLocation {
+            location: Some(window_location(window, TEST_WINDOW_SIZE / 2.0)),
+        },
+    ));
+    assert_eq!(pointer_hits(&mut app)[0].picks.len(), 1);
+
+    app.world.resource_mut::<RapierBackendSettings>().enabled = false;
+    // The first update clears the hover, and nothing is sent after that.
+    let hits = pointer_hits(&mut app);
+    assert_eq!(hits.len(), 1);
+    assert!(hits[0].picks.is_empty());
+    assert!(pointer_hits(&mut app).is_empty());
+}
@@ -0,0 +1,127 @@
+//! Checks when hits with no picks are sent, so that hovers are cleared.
+
+use bevy_math::{UVec2, Vec2, Vec3};
+use bevy_picking_core::backend::prelude::*;
+use bevy_rapier3d::prelude::*;
+use bevy_render::{camera::Viewport, prelude::*};
+use bevy_transform::prelude::*;
+
+use bevy_picking_rapier::{testing::*, RapierBackendSettings, RapierHovered, RapierRayPointer};
+
+#[test]
+fn empty_hits_are_sent_while_there_is_no_context() {
+    let mut app = test_app();
+    app.world
+        .resource_mut::<RapierBackendSettings>()
+        .emit_empty_hits = true;
+    let cuboid = spawn_pickable_cuboid(&mut app, Transform::default());
+    app.world.spawn((
+        TEST_POINTER,
+        RapierRayPointer {
+            origin: TEST_VIEW_ORIGIN,
+            direction: Vec3::NEG_Z,
+        },
+    ));
+    let hits = pointer_hits(&mut app);
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].picks.len(), 1);
+    assert_eq!(
+        app.world.resource::<RapierHovered>().get(TEST_POINTER),
+        Some(cuboid)
+    );
+
+    // As while physics is still loading.
+    let rapier_context = app.world.remove_resource::<RapierContext>().unwrap();
+    let hits = pointer_hits(&mut app);
+    assert_eq!(hits.len(), 1);
+    assert!(hits[0].picks.is_empty());
+    assert_eq!(
+        app.world.resource::<RapierHovered>().get(TEST_POINTER),
+        None
+    );
+
+    app.world.insert_resource(rapier_context);
+    let hits = pointer_hits(&mut app);
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].picks.len(), 1);
+}
+
+#[test]
+fn empty_hits_are_sent_for_pointers_outside_every_viewport() {
+    let mut app = test_app();
+    app.world
+        .resource_mut::<RapierBackendSettings>()
+        .emit_empty_hits = true;
+    let window = spawn_window(&mut app, 1.0);
+    // A camera rendering to the left half of the window.
+    spawn_camera(
+        &mut app,
+        Camera {
+            viewport: Some(Viewport {
+                physical_size: UVec2::new(400, 600),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        Transform::from_translation(TEST_VIEW_ORIGIN).looking_at(Vec3::ZERO, Vec3::Y),
+    );
+    let cuboid = spawn_pickable_cuboid(&mut app, Transform::default());
+    let pointer = app
+        .world
+        .spawn((
+            TEST_POINTER,
+            PointerLocation {
+                location: Some(window_location(window, Vec2::new(200.0, 300.0))),
+            },
+        ))
+        .id();
+    let hits = pointer_hits(&mut app);
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].picks.len(), 1);
+    assert_eq!(
+        app.world.resource::<RapierHovered>().get(TEST_POINTER),
+        Some(cuboid)
+    );
+
+    // Onto the right half of the window, which no camera renders to.
+    app.world.entity_mut(pointer).insert(PointerLocation {
+        location: Some(window_location(window, Vec2::new(600.0, 300.0))),
+    });
+    let hits = pointer_hits(&mut app);
+    assert_eq!(hits.len(), 1);
+    assert!(hits[0].picks.is_empty());
+    assert_eq!(
+        app.world.resource::<RapierHovered>().get(TEST_POINTER),
+        None
+    );
+}
+
+#[test]
+fn disabling_sends_empty_hits_once_even_when_emitting_empty_hits() {
+    let mut app = test_app();
+    app.world
+        .resource_mut::<RapierBackendSettings>()
+        .emit_empty_hits = true;
+    let cuboid = spawn_pickable_cuboid(&mut app, Transform::default());
+    app.world.spawn((
+        TEST_POINTER,
+        RapierRayPointer {
+            origin: TEST_VIEW_ORIGIN,
+            direction: Vec3::NEG_Z,
+        },
+    ));
+    assert_eq!(pointer_hits(&mut app)[0].picks.len(), 1);
+
+    app.world.resource_mut::<RapierBackendSettings>().enabled = false;
+    // The first update clears the hover, and nothing is sent after that.
+    let hits = pointer_hits(&mut app);
+    assert_eq!(hits.len(), 1);
+    assert!(hits[0].picks.is_empty());
+    assert!(pointer_hits(&mut app).is_empty());
+    assert!(pointer_hits(&mut app).is_empty());
+
+    app.world.resource_mut::<RapierBackendSettings>().enabled = true;
+    let hits = pointer_hits(&mut app);
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].picks[0].0, cuboid);
+}
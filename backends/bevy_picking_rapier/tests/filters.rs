@@ -0,0 +1,119 @@
+//! Checks the settings and components that keep colliders from being picked.
+
+use bevy_math::Vec3;
+use bevy_rapier3d::prelude::*;
+use bevy_render::{prelude::*, view::RenderLayers};
+use bevy_transform::prelude::*;
+
+use bevy_picking_rapier::{testing::*, RapierBackendSettings};
+
+#[test]
+fn disabled_colliders_are_passed_through() {
+    let mut app = test_app();
+    let front = spawn_pickable_cuboid(&mut app, Transform::from_xyz(0.0, 0.0, 2.0));
+    let behind = spawn_pickable_cuboid(&mut app, Transform::default());
+    // Without syncing the colliders, rapier still has the collider enabled, so only the backend's
+    // own check keeps it from being picked.
+    app.world.entity_mut(front).insert(ColliderDisabled);
+    assert_eq!(pick_center(&mut app), Some(behind));
+
+    app.world
+        .resource_mut::<RapierBackendSettings>()
+        .pick_disabled_colliders = true;
+    assert_eq!(pick_center(&mut app), Some(front));
+
+    // Once rapier disables the collider too, rays pass through it.
+    app.world
+        .resource_mut::<RapierBackendSettings>()
+        .pick_disabled_colliders = false;
+    sync_colliders(&mut app);
+    assert_eq!(pick_center(&mut app), Some(behind));
+}
+
+#[test]
+fn body_types_can_be_excluded() {
+    let mut app = test_app();
+    let cuboid = || Collider::cuboid(0.5, 0.5, 0.5);
+    let fixed = spawn_collider(
+        &mut app,
+        (RigidBody::Fixed, cuboid()),
+        Transform::from_xyz(0.0, 0.0, 4.0),
+    );
+    let kinematic = spawn_collider(
+        &mut app,
+        (RigidBody::KinematicPositionBased, cuboid()),
+        Transform::from_xyz(0.0, 0.0, 2.0),
+    );
+    let dynamic = spawn_collider(
+        &mut app,
+        (RigidBody::Dynamic, cuboid()),
+        Transform::default(),
+    );
+    assert_eq!(pick_center(&mut app), Some(fixed));
+
+    let mut settings = app.world.resource_mut::<RapierBackendSettings>();
+    settings.exclude_fixed = true;
+    assert_eq!(pick_center(&mut app), Some(kinematic));
+
+    let mut settings = app.world.resource_mut::<RapierBackendSettings>();
+    settings.exclude_kinematic = true;
+    assert_eq!(pick_center(&mut app), Some(dynamic));
+
+    let mut settings = app.world.resource_mut::<RapierBackendSettings>();
+    settings.exclude_dynamic = true;
+    assert_eq!(pick_center(&mut app), None);
+}
+
+#[test]
+fn entities_only_on_disabled_layers_are_passed_through() {
+    let mut app = test_app();
+    let cuboid = || Collider::cuboid(0.5, 0.5, 0.5);
+    let background = spawn_collider(
+        &mut app,
+        (cuboid(), RenderLayers::layer(1)),
+        Transform::from_xyz(0.0, 0.0, 4.0),
+    );
+    let shared = spawn_collider(
+        &mut app,
+        (cuboid(), RenderLayers::from_layers(&[0, 1])),
+        Transform::from_xyz(0.0, 0.0, 2.0),
+    );
+    spawn_pickable_cuboid(&mut app, Transform::default());
+    assert_eq!(pick_center(&mut app), Some(background));
+
+    app.world
+        .resource_mut::<RapierBackendSettings>()
+        .disabled_layers = RenderLayers::layer(1);
+    assert_eq!(pick_center(&mut app), Some(shared));
+
+    app.world
+        .resource_mut::<RapierBackendSettings>()
+        .disabled_layers = RenderLayers::from_layers(&[0, 1]);
+    assert_eq!(pick_center(&mut app), None);
+}
+
+#[test]
+fn cameras_only_on_disabled_layers_pick_nothing() {
+    let mut app = test_app();
+    let window = spawn_window(&mut app, 1.0);
+    let camera = spawn_camera(
+        &mut app,
+        Camera::default(),
+        Transform::from_translation(TEST_VIEW_ORIGIN).looking_at(Vec3::ZERO, Vec3::Y),
+    );
+    app.world.entity_mut(camera).insert(RenderLayers::layer(1));
+    let cuboid = spawn_collider(
+        &mut app,
+        (Collider::cuboid(0.5, 0.5, 0.5), RenderLayers::layer(1)),
+        Transform::default(),
+    );
+    let center = window_location(window, TEST_WINDOW_SIZE / 2.0);
+    let picks = pick_at(&mut app, center.clone());
+    assert_eq!(picks.len(), 1);
+    assert_eq!(picks[0].0, cuboid);
+
+    app.world
+        .resource_mut::<RapierBackendSettings>()
+        .disabled_layers = RenderLayers::layer(1);
+    assert!(pick_at(&mut app, center).is_empty());
+}
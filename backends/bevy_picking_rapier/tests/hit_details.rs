@@ -0,0 +1,162 @@
+//! Checks the rapier specific details kept for each hit, and that each is only found when asked
+//! for.
+
+use bevy_app::App;
+use bevy_math::{Quat, Ray, Vec3};
+use bevy_rapier3d::prelude::*;
+use bevy_transform::prelude::*;
+
+use bevy_picking_rapier::{testing::*, RapierBackendSettings, RapierPointerHit, RapierPointerHits};
+
+/// Picks from [`TEST_VIEW_ORIGIN`], offset by `offset`, towards the origin, returning the details
+/// of the nearest hit.
+fn pick_details(app: &mut App, offset: Vec3) -> Option<RapierPointerHit> {
+    let ray = Ray {
+        origin: TEST_VIEW_ORIGIN + offset,
+        direction: Vec3::NEG_Z,
+    };
+    let (entity, _) = *pick_along(app, ray).first()?;
+    app.world
+        .resource::<RapierPointerHits>()
+        .get(TEST_POINTER, entity)
+        .cloned()
+}
+
+#[test]
+fn no_details_are_kept_by_default() {
+    let mut app = test_app();
+    spawn_pickable_cuboid(&mut app, Transform::default());
+
+    assert!(pick_center(&mut app).is_some());
+    assert!(app.world.resource::<RapierPointerHits>().0.is_empty());
+}
+
+#[test]
+fn details_are_kept_for_each_hit() {
+    let mut app = test_app();
+    app.world
+        .resource_mut::<RapierBackendSettings>()
+        .provide_hit_details = true;
+    let cuboid = spawn_pickable_cuboid(&mut app, Transform::from_xyz(1.0, 0.0, 0.0));
+
+    let hit = pick_details(&mut app, Vec3::new(1.0, 0.25, 0.0)).expect("the cuboid was hit");
+    let handle = app.world.resource::<RapierContext>().entity2collider()[&cuboid];
+    assert_eq!(hit.entity, cuboid);
+    assert_eq!(hit.handle, handle);
+    assert!(!hit.is_sensor);
+    assert!((hit.depth - 9.5).abs() < 1e-4);
+    // None of the optional details were asked for.
+    assert_eq!(hit.local_point, None);
+    assert_eq!(hit.velocity, None);
+}
+
+#[test]
+fn local_points_are_provided_when_enabled() {
+    let mut app = test_app();
+    app.world
+        .resource_mut::<RapierBackendSettings>()
+        .provide_local_points = true;
+    spawn_pickable_cuboid(&mut app, Transform::from_xyz(1.0, 0.0, 0.0));
+
+    let hit = pick_details(&mut app, Vec3::new(1.0, 0.25, 0.0)).expect("the cuboid was hit");
+    let local_point = hit.local_point.expect("local points were asked for");
+    assert!(local_point.distance(Vec3::new(0.0, 0.25, 0.5)) < 1e-4);
+}
+
+#[test]
+#[allow(deprecated)]
+fn local_points_are_found_through_the_deprecated_resource_name() {
+    use bevy_picking_rapier::RapierLocalHits;
+
+    let mut app = test_app();
+    app.world
+        .resource_mut::<RapierBackendSettings>()
+        .provide_local_points = true;
+    let cuboid = spawn_pickable_cuboid(&mut app, Transform::from_xyz(1.0, 0.0, 0.0));
+
+    assert!(pick_details(&mut app, Vec3::new(1.0, 0.25, 0.0)).is_some());
+    let local_point = app
+        .world
+        .resource::<RapierLocalHits>()
+        .get(TEST_POINTER, cuboid)
+        .and_then(|hit| hit.local_point)
+        .expect("local points were asked for");
+    assert!(local_point.distance(Vec3::new(0.0, 0.25, 0.5)) < 1e-4);
+}
+
+#[test]
+fn velocities_are_provided_when_enabled() {
+    let mut app = test_app();
+    app.world
+        .resource_mut::<RapierBackendSettings>()
+        .provide_velocities = true;
+    spawn_collider(
+        &mut app,
+        (
+            RigidBody::Dynamic,
+            Velocity::linear(Vec3::X),
+            Collider::cuboid(0.5, 0.5, 0.5),
+        ),
+        Transform::default(),
+    );
+
+    let hit = pick_details(&mut app, Vec3::ZERO).expect("the cuboid was hit");
+    let velocity = hit.velocity.expect("velocities were asked for");
+    assert!(velocity.distance(Vec3::X) < 1e-4, "{velocity}");
+}
+
+#[test]
+fn compound_children_are_provided_when_enabled() {
+    let mut app = test_app();
+    let child = |x| {
+        (
+            Vec3::new(x, 0.0, 0.0),
+            Quat::IDENTITY,
+            Collider::cuboid(0.5, 0.5, 0.5),
+        )
+    };
+    let compound = || Collider::compound(vec![child(-1.0), child(1.0)]);
+    spawn_collider(&mut app, compound(), Transform::default());
+
+    app.world
+        .resource_mut::<RapierBackendSettings>()
+        .provide_hit_details = true;
+    let hit = pick_details(&mut app, Vec3::X).expect("the compound was hit");
+    assert_eq!(hit.compound_child, None);
+
+    app.world
+        .resource_mut::<RapierBackendSettings>()
+        .provide_compound_children = true;
+    let hit = pick_details(&mut app, Vec3::X).expect("the compound was hit");
+    assert_eq!(hit.compound_child, Some(1));
+}
+
+#[test]
+fn trimesh_hits_are_provided_when_enabled() {
+    let mut app = test_app();
+    app.world
+        .resource_mut::<RapierBackendSettings>()
+        .provide_trimesh_hits = true;
+    let vertices = [
+        Vec3::new(-1.0, -1.0, 0.0),
+        Vec3::new(1.0, -1.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+    ];
+    spawn_collider(
+        &mut app,
+        Collider::trimesh(vertices.to_vec(), vec![[0, 1, 2]]),
+        Transform::default(),
+    );
+
+    let hit = pick_details(&mut app, Vec3::ZERO).expect("the triangle was hit");
+    let trimesh = hit.trimesh.expect("trimesh hits were asked for");
+    assert_eq!(trimesh.triangle_index, 0);
+    assert_eq!(trimesh.vertices, vertices);
+    let point = vertices
+        .iter()
+        .zip(trimesh.barycentric.to_array())
+        .map(|(vertex, weight)| *vertex * weight)
+        .sum::<Vec3>();
+    assert!(point.distance(Vec3::ZERO) < 1e-4, "{point}");
+    assert_eq!(hit.smooth_normal, None);
+}
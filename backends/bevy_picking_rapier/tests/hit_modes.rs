@@ -0,0 +1,69 @@
+//! Checks which of the entities along a ray are reported.
+
+use bevy_app::App;
+use bevy_ecs::entity::Entity;
+use bevy_math::{Ray, Vec3};
+use bevy_rapier3d::prelude::*;
+use bevy_transform::prelude::*;
+
+use bevy_picking_rapier::{testing::*, HitMode, RapierBackendSettings};
+
+fn set_hit_mode(app: &mut App, hit_mode: HitMode, max_hits: usize) {
+    let mut settings = app.world.resource_mut::<RapierBackendSettings>();
+    settings.hit_mode = hit_mode;
+    settings.max_hits = max_hits;
+}
+
+/// The entities picked from the test view origin towards the origin, with the depth of their hits.
+fn picked(app: &mut App) -> Vec<(Entity, f32)> {
+    let ray = Ray {
+        origin: TEST_VIEW_ORIGIN,
+        direction: Vec3::NEG_Z,
+    };
+    pick_along(app, ray)
+        .into_iter()
+        .map(|(entity, hit)| (entity, hit.depth))
+        .collect()
+}
+
+#[test]
+fn all_hits_along_the_ray_are_reported_at_their_distance() {
+    let mut app = test_app();
+    let cuboids: Vec<_> = [0.0, -2.0, -4.0]
+        .map(|z| spawn_pickable_cuboid(&mut app, Transform::from_xyz(0.0, 0.0, z)))
+        .into();
+
+    assert_eq!(picked(&mut app).len(), 1);
+
+    set_hit_mode(&mut app, HitMode::All, 16);
+    let picks = picked(&mut app);
+    assert_eq!(picks.len(), 3);
+    for ((entity, depth), (cuboid, expected_depth)) in
+        picks.iter().zip(cuboids.iter().zip([9.5, 11.5, 13.5]))
+    {
+        assert_eq!(entity, cuboid);
+        assert!((depth - expected_depth).abs() < 1e-4, "{depth}");
+    }
+
+    set_hit_mode(&mut app, HitMode::All, 2);
+    let picks = picked(&mut app);
+    assert_eq!(picks.len(), 2);
+    assert_eq!(picks[0].0, cuboids[0]);
+    assert_eq!(picks[1].0, cuboids[1]);
+}
+
+#[test]
+fn stacked_colliders_close_together_are_all_reported() {
+    let mut app = test_app();
+    let plate = || Collider::cuboid(0.5, 0.5, 0.01);
+    let front = spawn_collider(&mut app, plate(), Transform::default());
+    let back = spawn_collider(&mut app, plate(), Transform::from_xyz(0.0, 0.0, -0.05));
+    // Touching the back plate.
+    let touching = spawn_collider(&mut app, plate(), Transform::from_xyz(0.0, 0.0, -0.07));
+
+    set_hit_mode(&mut app, HitMode::All, 16);
+    let picks = picked(&mut app);
+    let entities: Vec<_> = picks.iter().map(|(entity, _)| *entity).collect();
+    assert_eq!(entities, [front, back, touching]);
+    assert!((picks[1].1 - 10.04).abs() < 1e-4);
+}
@@ -0,0 +1,79 @@
+//! Checks where the rays that miss everything are recorded to end.
+
+use bevy_app::App;
+use bevy_ecs::entity::Entity;
+use bevy_math::Vec3;
+use bevy_render::prelude::*;
+use bevy_transform::prelude::*;
+
+use bevy_picking_rapier::{testing::*, RapierBackendSettings, RapierMissPoint, RapierMissPoints};
+
+/// Builds a test app recording the ends of rays that miss, with rays of up to `max_ray_distance`,
+/// a window, and a camera at the test view origin, looking at the origin. Returns the app, the
+/// window, and the camera.
+fn miss_app(max_ray_distance: Option<f32>) -> (App, Entity, Entity) {
+    let mut app = test_app();
+    {
+        let mut settings = app.world.resource_mut::<RapierBackendSettings>();
+        settings.emit_max_distance_point = true;
+        settings.max_ray_distance = max_ray_distance;
+    }
+    let window = spawn_window(&mut app, 1.0);
+    let camera = spawn_camera(
+        &mut app,
+        Camera::default(),
+        Transform::from_translation(TEST_VIEW_ORIGIN).looking_at(Vec3::ZERO, Vec3::Y),
+    );
+    // Off the ray through the center of the view.
+    spawn_pickable_cuboid(&mut app, Transform::from_xyz(5.0, 0.0, 0.0));
+    (app, window, camera)
+}
+
+/// Picks through the center of the view, returning where the ray was recorded to end, if it was.
+fn pick_center_miss(app: &mut App, window: Entity, camera: Entity) -> Option<RapierMissPoint> {
+    pick_at(app, window_location(window, TEST_WINDOW_SIZE / 2.0));
+    app.world
+        .resource::<RapierMissPoints>()
+        .get(TEST_POINTER, camera)
+}
+
+fn assert_miss_at(miss: RapierMissPoint, point: Vec3, direction: Vec3) {
+    assert!(miss.point.distance(point) < 1e-3, "{}", miss.point);
+    assert!(
+        miss.direction.distance(direction) < 1e-4,
+        "{}",
+        miss.direction
+    );
+}
+
+#[test]
+fn misses_end_at_the_max_distance_along_the_ray() {
+    let (mut app, window, camera) = miss_app(Some(20.0));
+    let miss = pick_center_miss(&mut app, window, camera).expect("the miss is recorded");
+    // The ray starts at the near plane, 9.9 along the z axis.
+    assert_miss_at(miss, Vec3::new(0.0, 0.0, -10.1), Vec3::NEG_Z);
+
+    // Hits aren't recorded as misses.
+    spawn_pickable_cuboid(&mut app, Transform::default());
+    assert_eq!(pick_center_miss(&mut app, window, camera), None);
+}
+
+#[test]
+fn misses_without_a_max_distance_are_not_recorded() {
+    let (mut app, window, camera) = miss_app(None);
+    assert_eq!(pick_center_miss(&mut app, window, camera), None);
+}
+
+#[test]
+fn reversed_misses_end_at_the_same_point() {
+    let (mut app, window, camera) = miss_app(Some(20.0));
+    let forward = pick_center_miss(&mut app, window, camera).expect("the miss is recorded");
+
+    // Casting towards the camera doesn't move the end of the ray, or flip its direction.
+    app.world
+        .resource_mut::<RapierBackendSettings>()
+        .reverse_cast = true;
+    let reversed = pick_center_miss(&mut app, window, camera).expect("the miss is recorded");
+    assert_eq!(reversed, forward);
+    assert_miss_at(reversed, Vec3::new(0.0, 0.0, -10.1), Vec3::NEG_Z);
+}
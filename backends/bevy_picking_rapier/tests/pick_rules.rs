@@ -0,0 +1,28 @@
+//! Checks that pick rules are applied, and stop applying once removed.
+
+use bevy_ecs::prelude::*;
+use bevy_transform::prelude::*;
+
+use bevy_picking_rapier::{testing::*, RapierPickRule};
+
+#[derive(Component)]
+struct Hidden;
+
+#[test]
+fn entities_denied_by_a_rule_are_not_picked() {
+    let mut app = test_app();
+    let front = spawn_pickable_cuboid(&mut app, Transform::from_xyz(0.0, 0.0, 2.0));
+    let back = spawn_pickable_cuboid(&mut app, Transform::default());
+    app.world.entity_mut(front).insert(Hidden);
+    let rule = app
+        .world
+        .spawn(RapierPickRule::new(|entity, world: &World| {
+            world.get::<Hidden>(entity).is_none()
+        }))
+        .id();
+    assert_eq!(pick_center(&mut app), Some(back));
+
+    // The entities the rule denied are picked again once it is gone.
+    app.world.despawn(rule);
+    assert_eq!(pick_center(&mut app), Some(front));
+}
@@ -0,0 +1,76 @@
+//! Checks that the rapier backend can be added to an app and run.
+
+use bevy_app::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use bevy_picking_core::CorePlugin;
+use bevy_picking_rapier::{
+    ConfiguredRapierBackend, HitMode, RapierBackend, RapierBackendSettings, SensorMode,
+};
+
+#[test]
+fn backend_runs_an_update() {
+    let mut app = App::new();
+    app.add_plugins((CorePlugin, RapierBackend));
+    app.update();
+
+    // The backend also runs once physics has added its context.
+    app.insert_resource(RapierContext::default());
+    app.update();
+}
+
+#[test]
+fn backend_keeps_existing_settings() {
+    let mut app = App::new();
+    app.insert_resource(RapierBackendSettings {
+        require_markers: true,
+        ..Default::default()
+    });
+    app.add_plugins((CorePlugin, RapierBackend));
+    assert!(
+        app.world
+            .resource::<RapierBackendSettings>()
+            .require_markers
+    );
+}
+
+#[test]
+fn configured_backend_starts_with_its_settings() {
+    let mut app = App::new();
+    // The configured settings replace any already in the app.
+    app.init_resource::<RapierBackendSettings>();
+    app.add_plugins((
+        CorePlugin,
+        ConfiguredRapierBackend::default()
+            .require_markers(true)
+            .hit_mode(HitMode::All)
+            .max_ray_distance(100.0)
+            .groups(CollisionGroups::new(Group::GROUP_2, Group::GROUP_3))
+            .sensor_mode(SensorMode::Exclude),
+    ));
+    app.insert_resource(RapierContext::default());
+    app.update();
+
+    let settings = app.world.resource::<RapierBackendSettings>();
+    assert!(settings.require_markers);
+    assert_eq!(settings.hit_mode, HitMode::All);
+    assert_eq!(settings.max_ray_distance, Some(100.0));
+    assert_eq!(
+        settings.groups,
+        Some(CollisionGroups::new(Group::GROUP_2, Group::GROUP_3))
+    );
+    assert_eq!(settings.sensor_mode, SensorMode::Exclude);
+}
+
+#[test]
+fn backend_with_settings_starts_with_them() {
+    let mut app = App::new();
+    app.add_plugins((
+        CorePlugin,
+        RapierBackend::with_settings(RapierBackendSettings {
+            max_hits: 3,
+            ..Default::default()
+        }),
+    ));
+    assert_eq!(app.world.resource::<RapierBackendSettings>().max_hits, 3);
+}
@@ -0,0 +1,52 @@
+//! Checks how far along the ray colliders can be picked.
+
+use bevy_math::Vec3;
+use bevy_rapier3d::prelude::*;
+use bevy_transform::prelude::*;
+
+use bevy_picking_rapier::{testing::*, RapierBackendSettings, RapierRayPointer};
+
+#[test]
+fn colliders_beyond_the_max_ray_distance_are_not_hit() {
+    let mut app = test_app();
+    // The front face of the cube is 9.5 units from the pointer.
+    let cuboid = spawn_pickable_cuboid(&mut app, Transform::default());
+    app.world.spawn((
+        TEST_POINTER,
+        RapierRayPointer {
+            origin: TEST_VIEW_ORIGIN,
+            direction: Vec3::NEG_Z,
+        },
+    ));
+
+    app.world
+        .resource_mut::<RapierBackendSettings>()
+        .max_ray_distance = Some(9.4);
+    assert!(pointer_hits(&mut app).is_empty());
+
+    app.world
+        .resource_mut::<RapierBackendSettings>()
+        .max_ray_distance = Some(9.6);
+    let hits = pointer_hits(&mut app);
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].picks.len(), 1);
+    assert_eq!(hits[0].picks[0].0, cuboid);
+}
+
+#[test]
+fn colliders_closer_than_the_min_ray_distance_are_passed_through() {
+    let mut app = test_app();
+    // The pointer starts inside the ball, as a chase camera would be inside its character.
+    let ball = spawn_collider(
+        &mut app,
+        Collider::ball(1.0),
+        Transform::from_translation(TEST_VIEW_ORIGIN),
+    );
+    let target = spawn_pickable_cuboid(&mut app, Transform::default());
+    assert_eq!(pick_center(&mut app), Some(ball));
+
+    app.world
+        .resource_mut::<RapierBackendSettings>()
+        .min_ray_distance = 1.5;
+    assert_eq!(pick_center(&mut app), Some(target));
+}
@@ -0,0 +1,96 @@
+//! Checks picking through cameras that render to images and to secondary windows.
+
+use bevy_app::App;
+use bevy_asset::{Assets, Handle};
+use bevy_math::{Vec2, Vec3};
+use bevy_picking_core::pointer::Location;
+use bevy_render::{
+    camera::{NormalizedRenderTarget, RenderTarget},
+    prelude::*,
+    render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use bevy_transform::prelude::*;
+use bevy_window::WindowRef;
+
+use bevy_picking_rapier::testing::*;
+
+/// Adds an image of `size` pixels for cameras to render to.
+fn add_image(app: &mut App, size: Vec2) -> Handle<Image> {
+    let image = Image::new_fill(
+        Extent3d {
+            width: size.x as u32,
+            height: size.y as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    app.world.resource_mut::<Assets<Image>>().add(image)
+}
+
+#[test]
+fn cameras_rendering_to_an_image_pick_at_pointers_on_the_image() {
+    let mut app = test_app();
+    let window = spawn_window(&mut app, 1.0);
+    let size = Vec2::new(400.0, 300.0);
+    let image = add_image(&mut app, size);
+    let camera = spawn_camera(
+        &mut app,
+        Camera {
+            target: RenderTarget::Image(image.clone()),
+            ..Default::default()
+        },
+        Transform::from_translation(TEST_VIEW_ORIGIN).looking_at(Vec3::ZERO, Vec3::Y),
+    );
+    let cuboid = spawn_pickable_cuboid(&mut app, Transform::default());
+
+    let on_image = Location {
+        target: NormalizedRenderTarget::Image(image),
+        position: size / 2.0,
+    };
+    let picks = pick_at(&mut app, on_image);
+    assert_eq!(picks.len(), 1);
+    assert_eq!(picks[0].0, cuboid);
+    assert_eq!(picks[0].1.camera, camera);
+
+    // The same position on the window isn't seen by the camera.
+    assert!(pick_at(&mut app, window_location(window, size / 2.0)).is_empty());
+}
+
+#[test]
+fn cameras_pick_through_their_own_window() {
+    let mut app = test_app();
+    let primary = spawn_window(&mut app, 1.0);
+    let secondary = spawn_window(&mut app, 1.0);
+    // Each camera looks at its own cube, far enough apart that neither can see the other's.
+    let primary_camera = spawn_camera(
+        &mut app,
+        Camera::default(),
+        Transform::from_translation(TEST_VIEW_ORIGIN).looking_at(Vec3::ZERO, Vec3::Y),
+    );
+    let secondary_target = Vec3::new(100.0, 0.0, 0.0);
+    let secondary_camera = spawn_camera(
+        &mut app,
+        Camera {
+            target: RenderTarget::Window(WindowRef::Entity(secondary)),
+            ..Default::default()
+        },
+        Transform::from_translation(TEST_VIEW_ORIGIN + secondary_target)
+            .looking_at(secondary_target, Vec3::Y),
+    );
+    let primary_cuboid = spawn_pickable_cuboid(&mut app, Transform::default());
+    let secondary_cuboid =
+        spawn_pickable_cuboid(&mut app, Transform::from_translation(secondary_target));
+
+    let center = TEST_WINDOW_SIZE / 2.0;
+    let picks = pick_at(&mut app, window_location(primary, center));
+    assert_eq!(picks.len(), 1);
+    assert_eq!(picks[0].0, primary_cuboid);
+    assert_eq!(picks[0].1.camera, primary_camera);
+
+    let picks = pick_at(&mut app, window_location(secondary, center));
+    assert_eq!(picks.len(), 1);
+    assert_eq!(picks[0].0, secondary_cuboid);
+    assert_eq!(picks[0].1.camera, secondary_camera);
+}
@@ -0,0 +1,68 @@
+//! Checks casting rays through cameras backwards, from their maximum distance towards the camera.
+
+use bevy_app::App;
+use bevy_ecs::entity::Entity;
+use bevy_math::Vec3;
+use bevy_render::prelude::*;
+use bevy_transform::prelude::*;
+
+use bevy_picking_rapier::{testing::*, RapierBackendSettings, RapierMissPoints};
+
+/// Builds a test app casting rays backwards from 20 units away, with a window and a camera at the
+/// test view origin, looking at the origin. Returns the app, the window, and the camera.
+fn reverse_app() -> (App, Entity, Entity) {
+    let mut app = test_app();
+    {
+        let mut settings = app.world.resource_mut::<RapierBackendSettings>();
+        settings.reverse_cast = true;
+        settings.max_ray_distance = Some(20.0);
+        settings.emit_max_distance_point = true;
+    }
+    let window = spawn_window(&mut app, 1.0);
+    let camera = spawn_camera(
+        &mut app,
+        Camera::default(),
+        Transform::from_translation(TEST_VIEW_ORIGIN).looking_at(Vec3::ZERO, Vec3::Y),
+    );
+    (app, window, camera)
+}
+
+#[test]
+fn reversed_rays_report_the_last_surface_first() {
+    let (mut app, window, _) = reverse_app();
+    spawn_pickable_cuboid(&mut app, Transform::default());
+    let back = spawn_pickable_cuboid(&mut app, Transform::from_xyz(0.0, 0.0, -4.0));
+
+    let picks = pick_at(&mut app, window_location(window, TEST_WINDOW_SIZE / 2.0));
+    assert_eq!(picks.len(), 1);
+    assert_eq!(picks[0].0, back);
+    // The ray starts at the near plane, 9.9 along the z axis, and ends 20 units later, at -10.1.
+    // The back face of the back cuboid is at -4.5.
+    let depth = picks[0].1.depth;
+    assert!((depth - 5.6).abs() < 1e-3, "{depth}");
+}
+
+#[test]
+fn reversed_rays_that_miss_end_away_from_the_camera() {
+    let (mut app, window, camera) = reverse_app();
+    // Off the ray, so it misses.
+    spawn_pickable_cuboid(&mut app, Transform::from_xyz(5.0, 0.0, 0.0));
+
+    assert!(pick_at(&mut app, window_location(window, TEST_WINDOW_SIZE / 2.0)).is_empty());
+    let miss = app
+        .world
+        .resource::<RapierMissPoints>()
+        .get(TEST_POINTER, camera)
+        .expect("the miss is recorded");
+    // The miss is at the far end of the ray through the camera, where the reversed ray starts.
+    assert!(
+        miss.point.distance(Vec3::new(0.0, 0.0, -10.1)) < 1e-3,
+        "{}",
+        miss.point
+    );
+    assert!(
+        miss.direction.distance(Vec3::NEG_Z) < 1e-4,
+        "{}",
+        miss.direction
+    );
+}
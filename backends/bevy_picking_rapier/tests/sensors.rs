@@ -0,0 +1,49 @@
+//! Checks which of a sensor and the solid collider behind it are picked in each sensor mode.
+
+use bevy_ecs::entity::Entity;
+use bevy_rapier3d::prelude::*;
+use bevy_transform::prelude::*;
+
+use bevy_picking_rapier::{testing::*, RapierBackendSettings, SensorMode};
+
+/// Returns the entity picked through a sensor in front of a solid cube, in `sensor_mode`, with the
+/// sensor and the solid cube.
+fn pick_through_sensor(sensor_mode: SensorMode) -> (Option<Entity>, Entity, Entity) {
+    let mut app = test_app();
+    app.world
+        .resource_mut::<RapierBackendSettings>()
+        .sensor_mode = sensor_mode;
+    let sensor = spawn_collider(
+        &mut app,
+        (Collider::cuboid(0.5, 0.5, 0.5), Sensor),
+        Transform::from_xyz(0.0, 0.0, 2.0),
+    );
+    let solid = spawn_pickable_cuboid(&mut app, Transform::default());
+    (pick_center(&mut app), sensor, solid)
+}
+
+#[test]
+fn sensors_are_picked_like_solid_colliders_when_included() {
+    let (picked, sensor, _) = pick_through_sensor(SensorMode::Include);
+    assert_eq!(picked, Some(sensor));
+}
+
+#[test]
+fn rays_pass_through_sensors_when_excluded() {
+    let (picked, _, solid) = pick_through_sensor(SensorMode::Exclude);
+    assert_eq!(picked, Some(solid));
+}
+
+#[test]
+fn only_sensors_are_picked_when_only_sensors_are_included() {
+    let (picked, sensor, _) = pick_through_sensor(SensorMode::Only);
+    assert_eq!(picked, Some(sensor));
+
+    // Without the sensor in front, the solid cube still isn't picked.
+    let mut app = test_app();
+    app.world
+        .resource_mut::<RapierBackendSettings>()
+        .sensor_mode = SensorMode::Only;
+    spawn_pickable_cuboid(&mut app, Transform::default());
+    assert_eq!(pick_center(&mut app), None);
+}
@@ -0,0 +1,58 @@
+//! Checks that pointers moving quickly hit the thin objects they pass over.
+
+use bevy_ecs::entity::Entity;
+use bevy_math::{Vec2, Vec3};
+use bevy_picking_core::backend::prelude::*;
+use bevy_rapier3d::prelude::*;
+use bevy_render::prelude::*;
+use bevy_transform::prelude::*;
+
+use bevy_picking_rapier::{testing::*, RapierBackendSettings};
+
+/// Moves a pointer across a thin wall in the middle of the window in a single frame, returning
+/// the wall and the entities picked once the pointer has moved.
+fn flick_across_a_wall(swept_pointer: bool) -> (Entity, Vec<Entity>) {
+    let mut app = test_app();
+    app.world
+        .resource_mut::<RapierBackendSettings>()
+        .swept_pointer = swept_pointer;
+    let window = spawn_window(&mut app, 1.0);
+    spawn_camera(
+        &mut app,
+        Camera::default(),
+        Transform::from_translation(TEST_VIEW_ORIGIN).looking_at(Vec3::ZERO, Vec3::Y),
+    );
+    let wall = spawn_collider(
+        &mut app,
+        Collider::cuboid(0.1, 2.0, 0.5),
+        Transform::default(),
+    );
+
+    let location = |x| PointerLocation {
+        location: Some(window_location(window, Vec2::new(x, 300.0))),
+    };
+    let pointer = app.world.spawn((TEST_POINTER, location(350.0))).id();
+    assert!(pointer_hits(&mut app)
+        .iter()
+        .all(|hits| hits.picks.is_empty()));
+
+    app.world.entity_mut(pointer).insert(location(450.0));
+    let picked = pointer_hits(&mut app)
+        .into_iter()
+        .flat_map(|hits| hits.picks)
+        .map(|(entity, _)| entity)
+        .collect();
+    (wall, picked)
+}
+
+#[test]
+fn swept_pointers_hit_thin_objects_they_pass_over() {
+    let (wall, picked) = flick_across_a_wall(true);
+    assert_eq!(picked, vec![wall]);
+}
+
+#[test]
+fn pointers_only_hit_what_they_end_up_over_without_sweeping() {
+    let (_, picked) = flick_across_a_wall(false);
+    assert!(picked.is_empty(), "{picked:?}");
+}
@@ -0,0 +1,65 @@
+//! Checks that the testing helpers set up picks the way a real app would.
+
+use bevy_math::{Ray, Vec3};
+use bevy_render::prelude::*;
+use bevy_transform::prelude::*;
+
+use bevy_picking_rapier::{testing::*, RapierRayPointer};
+
+#[test]
+fn cuboid_is_picked_where_it_is_spawned() {
+    let mut app = test_app();
+    let cuboid = spawn_pickable_cuboid(&mut app, Transform::from_xyz(3.0, 0.0, 0.0));
+
+    assert_eq!(pick_center(&mut app), None);
+    let ray = Ray {
+        origin: TEST_VIEW_ORIGIN + Vec3::X * 3.0,
+        direction: Vec3::NEG_Z,
+    };
+    assert_eq!(
+        pick_along(&mut app, ray).first().map(|(e, _)| *e),
+        Some(cuboid)
+    );
+}
+
+#[test]
+fn cuboid_is_picked_at_its_scaled_size() {
+    let mut app = test_app();
+    let cuboid = spawn_pickable_cuboid(&mut app, Transform::from_scale(Vec3::splat(4.0)));
+
+    // Well outside of the unscaled cube, but inside the scaled one.
+    let ray = Ray {
+        origin: TEST_VIEW_ORIGIN + Vec3::X * 1.5,
+        direction: Vec3::NEG_Z,
+    };
+    let picks = pick_along(&mut app, ray);
+    assert_eq!(picks.len(), 1);
+    assert_eq!(picks[0].0, cuboid);
+    assert!((picks[0].1.depth - 8.0).abs() < 1e-4);
+}
+
+#[test]
+fn camera_picks_through_the_window_center() {
+    let mut app = test_app();
+    let window = spawn_window(&mut app, 1.0);
+    let camera = spawn_camera(
+        &mut app,
+        Camera::default(),
+        Transform::from_translation(TEST_VIEW_ORIGIN).looking_at(Vec3::ZERO, Vec3::Y),
+    );
+    let cuboid = spawn_pickable_cuboid(&mut app, Transform::default());
+
+    let picks = pick_at(&mut app, window_location(window, TEST_WINDOW_SIZE / 2.0));
+    assert_eq!(picks.len(), 1);
+    assert_eq!(picks[0].0, cuboid);
+    assert_eq!(picks[0].1.camera, camera);
+}
+
+#[test]
+fn nothing_is_sent_for_a_pointer_that_misses() {
+    let mut app = test_app();
+    spawn_pickable_cuboid(&mut app, Transform::from_xyz(3.0, 0.0, 0.0));
+
+    app.world.spawn((TEST_POINTER, RapierRayPointer::default()));
+    assert!(pointer_hits(&mut app).is_empty());
+}
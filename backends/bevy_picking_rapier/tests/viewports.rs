@@ -0,0 +1,98 @@
+//! Checks picking through cameras that only render to part of a window.
+
+use bevy_math::{UVec2, Vec2, Vec3};
+use bevy_render::{camera::Viewport, prelude::*};
+use bevy_transform::prelude::*;
+
+use bevy_picking_rapier::testing::*;
+
+/// A camera rendering to the given part of the window, in physical pixels.
+fn viewport_camera(order: isize, position: UVec2, size: UVec2) -> Camera {
+    Camera {
+        order,
+        viewport: Some(Viewport {
+            physical_position: position,
+            physical_size: size,
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn split_screen_cameras_pick_through_their_own_viewport() {
+    let mut app = test_app();
+    let window = spawn_window(&mut app, 1.0);
+    let half = UVec2::new(400, 600);
+    // Each camera looks at its own cube, far enough apart that neither can see the other's.
+    let left_camera = spawn_camera(
+        &mut app,
+        viewport_camera(0, UVec2::ZERO, half),
+        Transform::from_translation(TEST_VIEW_ORIGIN).looking_at(Vec3::ZERO, Vec3::Y),
+    );
+    let right_target = Vec3::new(100.0, 0.0, 0.0);
+    let right_camera = spawn_camera(
+        &mut app,
+        viewport_camera(1, UVec2::new(400, 0), half),
+        Transform::from_translation(TEST_VIEW_ORIGIN + right_target)
+            .looking_at(right_target, Vec3::Y),
+    );
+    let left_cuboid = spawn_pickable_cuboid(&mut app, Transform::default());
+    let right_cuboid = spawn_pickable_cuboid(&mut app, Transform::from_translation(right_target));
+
+    // The center of each half of the window is the center of that camera's view.
+    let picks = pick_at(&mut app, window_location(window, Vec2::new(200.0, 300.0)));
+    assert_eq!(picks.len(), 1);
+    assert_eq!(picks[0].0, left_cuboid);
+    assert_eq!(picks[0].1.camera, left_camera);
+
+    let picks = pick_at(&mut app, window_location(window, Vec2::new(600.0, 300.0)));
+    assert_eq!(picks.len(), 1);
+    assert_eq!(picks[0].0, right_cuboid);
+    assert_eq!(picks[0].1.camera, right_camera);
+    // Rays through cameras start at the near plane, 0.1 in front of the camera.
+    let depth = picks[0].1.depth;
+    assert!((depth - 9.4).abs() < 1e-3, "{depth}");
+}
+
+#[test]
+fn zero_size_viewports_are_not_picked_through() {
+    let mut app = test_app();
+    let window = spawn_window(&mut app, 1.0);
+    // Like a collapsed split, right where the pointer is.
+    let center = UVec2::new(400, 300);
+    spawn_camera(
+        &mut app,
+        viewport_camera(0, center, UVec2::ZERO),
+        Transform::from_translation(TEST_VIEW_ORIGIN).looking_at(Vec3::ZERO, Vec3::Y),
+    );
+    spawn_pickable_cuboid(&mut app, Transform::default());
+
+    let picks = pick_at(&mut app, window_location(window, center.as_vec2()));
+    assert!(picks.is_empty(), "{picks:?}");
+}
+
+#[test]
+fn pointers_pick_at_their_logical_position_on_scaled_windows() {
+    let mut app = test_app();
+    // The window is twice as many physical pixels across as logical pixels.
+    let window = spawn_window(&mut app, 2.0);
+    let physical_half = UVec2::new(800, 1200);
+    spawn_camera(
+        &mut app,
+        viewport_camera(0, UVec2::new(800, 0), physical_half),
+        Transform::from_translation(TEST_VIEW_ORIGIN).looking_at(Vec3::ZERO, Vec3::Y),
+    );
+    let cuboid = spawn_pickable_cuboid(&mut app, Transform::default());
+
+    // The center of the right half of the window, in logical pixels, is the center of the view.
+    let picks = pick_at(&mut app, window_location(window, Vec2::new(600.0, 300.0)));
+    assert_eq!(picks.len(), 1);
+    let (entity, hit) = &picks[0];
+    assert_eq!(*entity, cuboid);
+    let position = hit.position.unwrap();
+    assert!(position.distance(Vec3::Z * 0.5) < 1e-3, "{position}");
+
+    // The left half of the window, which the camera doesn't render to.
+    assert!(pick_at(&mut app, window_location(window, Vec2::new(300.0, 300.0))).is_empty());
+}